@@ -0,0 +1,328 @@
+// Async executor benchmark
+// A minimal single-threaded executor polling `async fn` state machines with
+// wakers built from a hand-written `RawWakerVTable`. Producer tasks send
+// messages, some with tainted values, through channels to mapper tasks, which
+// transform them across `await` points before forwarding them to a consumer.
+// Tainted values are stored in the compiler-generated futures while suspended.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const PRODUCERS: usize = 4;
+const TAINTED_PERIOD: usize = 3;
+
+type ReadyQueue = Rc<RefCell<VecDeque<usize>>>;
+
+/// Schedules its task again when woken.
+struct TaskWaker {
+    id: usize,
+    ready: ReadyQueue,
+}
+
+impl TaskWaker {
+    fn schedule(&self) {
+        self.ready.borrow_mut().push_back(self.id);
+    }
+}
+
+// The executor is single-threaded, so wakers can share their task through an
+// `Rc` even though `Waker` is `Send`.
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone_waker, wake, wake_by_ref, drop_waker);
+
+unsafe fn clone_waker(data: *const ()) -> RawWaker {
+    unsafe { Rc::increment_strong_count(data as *const TaskWaker) };
+    RawWaker::new(data, &VTABLE)
+}
+
+unsafe fn wake(data: *const ()) {
+    let waker = unsafe { Rc::from_raw(data as *const TaskWaker) };
+    waker.schedule();
+}
+
+unsafe fn wake_by_ref(data: *const ()) {
+    let waker = unsafe { &*(data as *const TaskWaker) };
+    waker.schedule();
+}
+
+unsafe fn drop_waker(data: *const ()) {
+    drop(unsafe { Rc::from_raw(data as *const TaskWaker) });
+}
+
+fn new_waker(task: Rc<TaskWaker>) -> Waker {
+    let data = Rc::into_raw(task) as *const ();
+    unsafe { Waker::from_raw(RawWaker::new(data, &VTABLE)) }
+}
+
+struct Executor {
+    tasks: Vec<Option<Pin<Box<dyn Future<Output = ()>>>>>,
+    ready: ReadyQueue,
+}
+
+impl Executor {
+    fn new() -> Self {
+        Executor {
+            tasks: Vec::new(),
+            ready: Rc::new(RefCell::new(VecDeque::new())),
+        }
+    }
+
+    fn spawn(&mut self, future: impl Future<Output = ()> + 'static) {
+        self.ready.borrow_mut().push_back(self.tasks.len());
+        self.tasks.push(Some(Box::pin(future)));
+    }
+
+    /// Polls ready tasks until none is left, returning the number of polls.
+    fn run(&mut self) -> usize {
+        let mut polls = 0;
+        loop {
+            let Some(id) = self.ready.borrow_mut().pop_front() else {
+                break;
+            };
+            // A task may have been woken again after completing.
+            let Some(task) = self.tasks[id].as_mut() else {
+                continue;
+            };
+
+            let waker = new_waker(Rc::new(TaskWaker {
+                id,
+                ready: self.ready.clone(),
+            }));
+            let mut cx = Context::from_waker(&waker);
+            polls += 1;
+            if task.as_mut().poll(&mut cx).is_ready() {
+                self.tasks[id] = None;
+            }
+        }
+
+        assert!(self.tasks.iter().all(Option::is_none), "Deadlock");
+        polls
+    }
+}
+
+/// Returns `Pending` once, rescheduling the task right away.
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+struct Channel<T> {
+    queue: VecDeque<T>,
+    receiver: Option<Waker>,
+    senders: usize,
+}
+
+/// Unbounded channel, closed once all senders are dropped.
+fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let channel = Rc::new(RefCell::new(Channel {
+        queue: VecDeque::new(),
+        receiver: None,
+        senders: 1,
+    }));
+    (
+        Sender {
+            channel: channel.clone(),
+        },
+        Receiver { channel },
+    )
+}
+
+struct Sender<T> {
+    channel: Rc<RefCell<Channel<T>>>,
+}
+
+impl<T> Sender<T> {
+    fn send(&self, value: T) {
+        let mut channel = self.channel.borrow_mut();
+        channel.queue.push_back(value);
+        if let Some(waker) = channel.receiver.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.channel.borrow_mut().senders += 1;
+        Sender {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut channel = self.channel.borrow_mut();
+        channel.senders -= 1;
+        if channel.senders == 0
+            && let Some(waker) = channel.receiver.take()
+        {
+            waker.wake();
+        }
+    }
+}
+
+struct Receiver<T> {
+    channel: Rc<RefCell<Channel<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Resolves to the next value, or `None` once the channel is closed.
+    fn recv(&self) -> Recv<'_, T> {
+        Recv { receiver: self }
+    }
+}
+
+struct Recv<'a, T> {
+    receiver: &'a Receiver<T>,
+}
+
+impl<T> Future for Recv<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut channel = self.receiver.channel.borrow_mut();
+        if let Some(value) = channel.queue.pop_front() {
+            Poll::Ready(Some(value))
+        } else if channel.senders == 0 {
+            Poll::Ready(None)
+        } else {
+            channel.receiver = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Message {
+    id: usize,
+    value: i32,
+    // Whether the value is tainted, for the checks.
+    tainted: bool,
+}
+
+fn initial_value(id: usize) -> i32 {
+    (id * 7 + 1) as i32
+}
+
+fn transformed_value(id: usize) -> i32 {
+    let x = initial_value(id);
+    (x * 3 + 5) * 2 - x
+}
+
+async fn produce(producer: usize, n: usize, tx: Sender<Message>) {
+    for j in 0..n {
+        let id = producer * n + j;
+        let tainted = (producer + j).is_multiple_of(TAINTED_PERIOD);
+        let value = initial_value(id);
+        tx.send(Message {
+            id,
+            value: if tainted {
+                unsafe { taint_i32(value) }
+            } else {
+                value
+            },
+            tainted,
+        });
+        yield_now().await;
+    }
+}
+
+/// Computes `transformed_value`, keeping intermediate values across `await`
+/// points.
+async fn transform(message: Message) -> Message {
+    let tripled = message.value * 3;
+    yield_now().await;
+    let shifted = tripled + 5;
+    yield_now().await;
+    Message {
+        value: shifted * 2 - message.value,
+        ..message
+    }
+}
+
+async fn map(rx: Receiver<Message>, tx: Sender<Message>) {
+    while let Some(message) = rx.recv().await {
+        tx.send(transform(message).await);
+    }
+}
+
+async fn consume(rx: Receiver<Message>, results: Rc<RefCell<Vec<Message>>>, done: Rc<Cell<bool>>) {
+    while let Some(message) = rx.recv().await {
+        results.borrow_mut().push(message);
+    }
+    done.set(true);
+}
+
+fn benchmark(n: usize) -> i32 {
+    let mut executor = Executor::new();
+    let results = Rc::new(RefCell::new(Vec::new()));
+    let done = Rc::new(Cell::new(false));
+
+    let (results_tx, results_rx) = channel();
+    executor.spawn(consume(results_rx, results.clone(), done.clone()));
+    for producer in 0..PRODUCERS {
+        let (tx, rx) = channel();
+        executor.spawn(map(rx, results_tx.clone()));
+        executor.spawn(produce(producer, n, tx));
+    }
+    drop(results_tx);
+
+    let polls = executor.run();
+    assert!(done.get());
+
+    let results = results.borrow();
+    assert_eq!(results.len(), PRODUCERS * n);
+    let mut seen = vec![false; PRODUCERS * n];
+    let mut tainted_sum: i32 = 0;
+    let mut clean_sum: i32 = 0;
+    for message in results.iter() {
+        assert!(!seen[message.id]);
+        seen[message.id] = true;
+        assert_eq!(message.value, transformed_value(message.id));
+        if message.tainted {
+            unsafe { assert_is_tainted_i32(message.value) };
+            tainted_sum = tainted_sum.wrapping_add(message.value);
+        } else {
+            unsafe { assert_is_not_tainted_i32(message.value) };
+            clean_sum = clean_sum.wrapping_add(message.value);
+        }
+    }
+    if n > 0 {
+        unsafe { assert_is_tainted_i32(tainted_sum) };
+    }
+    unsafe { assert_is_not_tainted_i32(clean_sum) };
+
+    polls as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
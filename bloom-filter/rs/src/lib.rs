@@ -0,0 +1,171 @@
+// Bloom filter benchmark
+// Inserts strings, some of them tainted, into a Bloom filter backed by a `u64`
+// array and into a `HashSet`, then queries both with untainted strings.
+//
+// Inserting a tainted string shifts a tainted bit into its words, so a query
+// anding the probed bits is tainted iff one of its words was set by a tainted
+// string. The short-circuiting query and `HashSet::contains` are only used as
+// branch conditions, recording the index of each query they answer positively:
+// the recorded indices depend on the tainted strings through control flow only
+// and stay untainted.
+
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::BuildHasherDefault;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const BITS_PER_KEY: usize = 10;
+const HASHES: u64 = 7;
+const TAINTED_PERIOD: usize = 64;
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv1a(key: &[u8]) -> u64 {
+    let mut hash = FNV_OFFSET;
+    for &byte in key {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Finalizer of MurmurHash3, used to derive the second hash.
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^ (hash >> 33)
+}
+
+struct BloomFilter {
+    words: Vec<u64>,
+    bits: u64,
+}
+
+impl BloomFilter {
+    fn new(bits: usize) -> Self {
+        BloomFilter {
+            words: vec![0; bits.div_ceil(64)],
+            bits: bits as u64,
+        }
+    }
+
+    /// Returns the bits to probe for `key`, by double hashing.
+    fn probes(&self, key: &[u8]) -> impl Iterator<Item = u64> + use<> {
+        let h1 = fnv1a(key);
+        let h2 = mix(h1) | 1;
+        let bits = self.bits;
+        (0..HASHES).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bits)
+    }
+
+    fn insert(&mut self, key: &[u8]) {
+        for bit in self.probes(key) {
+            self.words[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Ands the probed bits, returning 1 iff all are set.
+    fn query_bitwise(&self, key: &[u8]) -> u64 {
+        let mut found = 1;
+        for bit in self.probes(key) {
+            found &= self.words[(bit / 64) as usize] >> (bit % 64);
+        }
+        found & 1
+    }
+
+    /// Returns whether all probed bits are set, stopping at the first unset.
+    fn query_short_circuit(&self, key: &[u8]) -> bool {
+        for bit in self.probes(key) {
+            if self.words[(bit / 64) as usize] & (1 << (bit % 64)) == 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns the words probed for `key`.
+    fn probed_words(&self, key: &[u8]) -> Vec<usize> {
+        self.probes(key).map(|bit| (bit / 64) as usize).collect()
+    }
+}
+
+fn key(i: usize) -> Vec<u8> {
+    format!("key-{}-{}", i, (i as u32).wrapping_mul(2654435761) % 1000).into_bytes()
+}
+
+fn taint_key(key: Vec<u8>) -> Vec<u8> {
+    key.into_iter()
+        .map(|byte| unsafe { taint_i32(byte as i32) as u8 })
+        .collect()
+}
+
+fn benchmark(n: usize) -> i32 {
+    let mut filter = BloomFilter::new(n * BITS_PER_KEY);
+    let mut set: HashSet<Vec<u8>, BuildHasherDefault<DefaultHasher>> = HashSet::default();
+    // Whether each word was set by a tainted key, for the checks.
+    let mut tainted_words = vec![false; filter.words.len()];
+
+    for i in 0..n {
+        let key = key(2 * i);
+        if i.is_multiple_of(TAINTED_PERIOD) {
+            for word in filter.probed_words(&key) {
+                tainted_words[word] = true;
+            }
+            let key = taint_key(key);
+            filter.insert(&key);
+            set.insert(key);
+        } else {
+            filter.insert(&key);
+            set.insert(key);
+        }
+    }
+
+    // Query the inserted keys, with even numbers, and as many others.
+    let mut positives = Vec::new();
+    let mut members = Vec::new();
+    for i in 0..2 * n {
+        let key = key(i);
+
+        let found = filter.query_bitwise(&key);
+        let tainted = filter.probed_words(&key).iter().any(|&w| tainted_words[w]);
+        if tainted {
+            unsafe { assert_is_tainted_i32(found as i32) };
+        } else {
+            unsafe { assert_is_not_tainted_i32(found as i32) };
+        }
+
+        // The answers may be computed from tainted words or hash tags, and are
+        // only branched on.
+        if filter.query_short_circuit(&key) {
+            positives.push(i);
+        }
+        if set.contains(&key) {
+            members.push(i);
+        }
+        assert_eq!(positives.last() == Some(&i), found == 1);
+    }
+
+    assert!(members.iter().copied().eq((0..2 * n).step_by(2)));
+    assert!(
+        members
+            .iter()
+            .all(|member| positives.binary_search(member).is_ok())
+    );
+    for &i in positives.iter().chain(&members) {
+        unsafe { assert_is_not_tainted_i32(i as i32) };
+    }
+
+    (positives.len() - members.len()) as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
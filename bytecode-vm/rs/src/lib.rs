@@ -0,0 +1,375 @@
+// Bytecode VM benchmark
+// A small stack-based bytecode interpreter. The dispatch loop matches on dense
+// opcode numbers, which compiles to a `br_table`, so tainted opcodes become
+// tainted `br_table` selectors. Three programs are run: one with tainted
+// operands, one with tainted opcodes and one whose bytecode is fully tainted.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const OP_PUSH: i32 = 0;
+const OP_LOAD: i32 = 1;
+const OP_STORE: i32 = 2;
+const OP_ADD: i32 = 3;
+const OP_SUB: i32 = 4;
+const OP_MUL: i32 = 5;
+const OP_DUP: i32 = 6;
+const OP_SWAP: i32 = 7;
+const OP_DROP: i32 = 8;
+const OP_LT: i32 = 9;
+const OP_JMP: i32 = 10;
+const OP_JZ: i32 = 11;
+const OP_JNZ: i32 = 12;
+const OP_HALT: i32 = 13;
+
+const LOCALS_COUNT: usize = 8;
+
+// Weight used by the weighted sum program, pushed as a tainted operand.
+const WEIGHT: i32 = 7;
+// Coefficients of the polynomial evaluated by the Horner program.
+const COEFFICIENTS: [i32; 4] = [5, -3, 2, 1];
+
+struct Program {
+    code: Vec<i32>,
+    opcode_positions: Vec<usize>,
+}
+
+impl Program {
+    /// Taints every opcode word, leaving operands untouched.
+    fn taint_opcodes(&mut self) {
+        for &pos in &self.opcode_positions {
+            self.code[pos] = unsafe { taint_i32(self.code[pos]) };
+        }
+    }
+
+    /// Taints every word of the bytecode, opcodes and operands alike.
+    fn taint_all(&mut self) {
+        for word in self.code.iter_mut() {
+            *word = unsafe { taint_i32(*word) };
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Label(usize);
+
+/// Builds bytecode, resolving jump targets once all labels are bound.
+struct Assembler {
+    code: Vec<i32>,
+    opcode_positions: Vec<usize>,
+    labels: Vec<usize>,
+    fixups: Vec<(usize, Label)>,
+}
+
+impl Assembler {
+    fn new() -> Self {
+        Assembler {
+            code: Vec::new(),
+            opcode_positions: Vec::new(),
+            labels: Vec::new(),
+            fixups: Vec::new(),
+        }
+    }
+
+    fn op(&mut self, opcode: i32) -> &mut Self {
+        self.opcode_positions.push(self.code.len());
+        self.code.push(opcode);
+        self
+    }
+
+    fn op_with(&mut self, opcode: i32, operand: i32) -> &mut Self {
+        self.op(opcode);
+        self.code.push(operand);
+        self
+    }
+
+    fn push(&mut self, value: i32) -> &mut Self {
+        self.op_with(OP_PUSH, value)
+    }
+
+    fn load(&mut self, slot: i32) -> &mut Self {
+        self.op_with(OP_LOAD, slot)
+    }
+
+    fn store(&mut self, slot: i32) -> &mut Self {
+        self.op_with(OP_STORE, slot)
+    }
+
+    fn jump(&mut self, opcode: i32, label: Label) -> &mut Self {
+        self.op_with(opcode, 0);
+        self.fixups.push((self.code.len() - 1, label));
+        self
+    }
+
+    fn new_label(&mut self) -> Label {
+        self.labels.push(usize::MAX);
+        Label(self.labels.len() - 1)
+    }
+
+    fn bind(&mut self, label: Label) -> &mut Self {
+        self.labels[label.0] = self.code.len();
+        self
+    }
+
+    fn finish(mut self) -> Program {
+        for &(pos, label) in &self.fixups {
+            self.code[pos] = self.labels[label.0] as i32;
+        }
+        Program {
+            code: self.code,
+            opcode_positions: self.opcode_positions,
+        }
+    }
+}
+
+struct Vm {
+    stack: Vec<i32>,
+    locals: [i32; LOCALS_COUNT],
+    steps: i32,
+}
+
+impl Vm {
+    fn new() -> Self {
+        Vm {
+            stack: Vec::with_capacity(64),
+            locals: [0; LOCALS_COUNT],
+            steps: 0,
+        }
+    }
+
+    fn pop(&mut self) -> i32 {
+        self.stack.pop().expect("Stack underflow")
+    }
+
+    fn run(&mut self, code: &[i32]) {
+        let mut pc = 0;
+        self.stack.clear();
+
+        loop {
+            self.steps += 1;
+            match code[pc] {
+                OP_PUSH => {
+                    self.stack.push(code[pc + 1]);
+                    pc += 2;
+                }
+                OP_LOAD => {
+                    self.stack.push(self.locals[code[pc + 1] as usize]);
+                    pc += 2;
+                }
+                OP_STORE => {
+                    self.locals[code[pc + 1] as usize] = self.pop();
+                    pc += 2;
+                }
+                OP_ADD => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack.push(a.wrapping_add(b));
+                    pc += 1;
+                }
+                OP_SUB => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack.push(a.wrapping_sub(b));
+                    pc += 1;
+                }
+                OP_MUL => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack.push(a.wrapping_mul(b));
+                    pc += 1;
+                }
+                OP_DUP => {
+                    let a = self.pop();
+                    self.stack.push(a);
+                    self.stack.push(a);
+                    pc += 1;
+                }
+                OP_SWAP => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack.push(b);
+                    self.stack.push(a);
+                    pc += 1;
+                }
+                OP_DROP => {
+                    self.pop();
+                    pc += 1;
+                }
+                OP_LT => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack.push((a < b) as i32);
+                    pc += 1;
+                }
+                OP_JMP => {
+                    pc = code[pc + 1] as usize;
+                }
+                OP_JZ => {
+                    if self.pop() == 0 {
+                        pc = code[pc + 1] as usize;
+                    } else {
+                        pc += 2;
+                    }
+                }
+                OP_JNZ => {
+                    if self.pop() != 0 {
+                        pc = code[pc + 1] as usize;
+                    } else {
+                        pc += 2;
+                    }
+                }
+                OP_HALT => return,
+                op => panic!("Unknown opcode {} at {}", op, pc),
+            }
+        }
+    }
+}
+
+/// Emits `local[slot] -= 1; jmp label`.
+fn emit_decrement_and_loop(asm: &mut Assembler, slot: i32, label: Label) {
+    asm.load(slot)
+        .push(1)
+        .op(OP_SUB)
+        .store(slot)
+        .jump(OP_JMP, label);
+}
+
+/// `sum = 0; for i in (1..=n).rev() { sum += i * weight }`, leaving `[sum, i]`
+/// on the stack.
+fn weighted_sum_program(n: i32, weight: i32) -> Program {
+    let mut asm = Assembler::new();
+    let head = asm.new_label();
+    let end = asm.new_label();
+
+    asm.push(n).store(0).push(0).store(1);
+    asm.bind(head).load(0).jump(OP_JZ, end);
+    asm.load(1)
+        .load(0)
+        .push(weight)
+        .op(OP_MUL)
+        .op(OP_ADD)
+        .store(1);
+    emit_decrement_and_loop(&mut asm, 0, head);
+    asm.bind(end).load(1).load(0).op(OP_HALT);
+
+    asm.finish()
+}
+
+/// Iterative Fibonacci, leaving `[fib(n)]` on the stack.
+fn fibonacci_program(n: i32) -> Program {
+    let mut asm = Assembler::new();
+    let head = asm.new_label();
+    let end = asm.new_label();
+
+    asm.push(n).store(0).push(0).store(1).push(1).store(2);
+    asm.bind(head).load(0).jump(OP_JZ, end);
+    asm.load(1).load(2).op(OP_DUP).store(1).op(OP_ADD).store(2);
+    emit_decrement_and_loop(&mut asm, 0, head);
+    asm.bind(end).load(1).op(OP_HALT);
+
+    asm.finish()
+}
+
+/// Sums the polynomial `COEFFICIENTS` evaluated with Horner's rule at
+/// `x = n, n - 1, ..., 1`, leaving `[sum]` on the stack. The loop condition
+/// uses `LT`, `JNZ`, `SWAP` and `DROP` to exercise the remaining opcodes.
+fn horner_program(n: i32) -> Program {
+    let mut asm = Assembler::new();
+    let head = asm.new_label();
+    let body = asm.new_label();
+    let end = asm.new_label();
+
+    asm.push(n).store(0).push(0).store(1);
+    asm.bind(head)
+        .push(0)
+        .load(0)
+        .op(OP_LT)
+        .jump(OP_JNZ, body)
+        .jump(OP_JMP, end);
+    asm.bind(body).push(COEFFICIENTS[3]);
+    for &coefficient in COEFFICIENTS[..3].iter().rev() {
+        asm.load(0).op(OP_MUL).push(coefficient).op(OP_ADD);
+    }
+    asm.load(1)
+        .op(OP_SWAP)
+        .op(OP_ADD)
+        .store(1)
+        .push(-1)
+        .op(OP_DROP);
+    emit_decrement_and_loop(&mut asm, 0, head);
+    asm.bind(end).load(1).op(OP_HALT);
+
+    asm.finish()
+}
+
+fn expected_weighted_sum(n: i32) -> i32 {
+    (1..=n).fold(0i32, |sum, i| sum.wrapping_add(i.wrapping_mul(WEIGHT)))
+}
+
+fn expected_fibonacci(n: i32) -> i32 {
+    let (mut a, mut b) = (0i32, 1i32);
+    for _ in 0..n {
+        (a, b) = (b, a.wrapping_add(b));
+    }
+    a
+}
+
+fn expected_horner(n: i32) -> i32 {
+    let mut sum = 0i32;
+    for x in 1..=n {
+        let mut value = COEFFICIENTS[3];
+        for &coefficient in COEFFICIENTS[..3].iter().rev() {
+            value = value.wrapping_mul(x).wrapping_add(coefficient);
+        }
+        sum = sum.wrapping_add(value);
+    }
+    sum
+}
+
+fn benchmark(n: i32) -> i32 {
+    let mut vm = Vm::new();
+
+    // Tainted operand: only values computed from the weight are tainted.
+    let program = weighted_sum_program(n, unsafe { taint_i32(WEIGHT) });
+    vm.run(&program.code);
+    assert_eq!(vm.stack, [expected_weighted_sum(n), 0]);
+    // Without iterations, the sum is the constant it starts from.
+    if n > 0 {
+        unsafe { assert_is_tainted_i32(vm.stack[0]) };
+    } else {
+        unsafe { assert_is_not_tainted_i32(vm.stack[0]) };
+    }
+    unsafe { assert_is_not_tainted_i32(vm.stack[1]) };
+    unsafe { assert_is_not_tainted_i32(vm.locals[0]) };
+
+    // Tainted opcodes: they only select which handler runs, so no value
+    // computed by the program is explicitly derived from them.
+    let mut program = fibonacci_program(n);
+    program.taint_opcodes();
+    vm.run(&program.code);
+    assert_eq!(vm.stack, [expected_fibonacci(n)]);
+    unsafe { assert_is_not_tainted_i32(vm.stack[0]) };
+    for &pos in &program.opcode_positions {
+        unsafe { assert_is_tainted_i32(program.code[pos]) };
+    }
+
+    // Fully tainted bytecode: the loop counter comes from a tainted operand too.
+    let mut program = horner_program(n);
+    program.taint_all();
+    vm.run(&program.code);
+    assert_eq!(vm.stack, [expected_horner(n)]);
+    unsafe { assert_is_tainted_i32(vm.stack[0]) };
+    unsafe { assert_is_tainted_i32(vm.locals[0]) };
+
+    unsafe { assert_is_not_tainted_i32(vm.steps) };
+    vm.steps
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n)
+}
@@ -0,0 +1,122 @@
+// Checksum benchmark
+// Computes CRC32 and Adler-32 over the same blocks of random bytes, some of
+// which contain a tainted run. Adler-32 adds the bytes, so its checksum of a
+// block is tainted iff the block has a tainted run. The table-driven CRC32 only
+// uses the bytes to index its lookup table, and the entries it loads are
+// constants, so none of its checksums is tainted.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const BLOCK_SIZE: usize = 4096;
+const TAINTED_RUN_START: usize = 1000;
+const TAINTED_RUN_LENGTH: usize = 64;
+const CRC_POLYNOMIAL: u32 = 0xEDB88320;
+const ADLER_MODULUS: u32 = 65521;
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 == 1 {
+                CRC_POLYNOMIAL ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+    table
+}
+
+fn crc32(table: &[u32; 256], data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc = table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let mut a = 1;
+    let mut b = 0;
+    for &byte in data {
+        a = (a + byte as u32) % ADLER_MODULUS;
+        b = (b + a) % ADLER_MODULUS;
+    }
+    (b << 16) | a
+}
+
+/// Fills `blocks` blocks with random bytes, tainting a run in every third one.
+fn setup_blocks(blocks: usize) -> Vec<Vec<u8>> {
+    let mut rng = Random::new();
+    (0..blocks)
+        .map(|block| {
+            let tainted = block.is_multiple_of(3);
+            (0..BLOCK_SIZE)
+                .map(|i| {
+                    let byte = rng.gen_random(256.0) as u8;
+                    let in_run =
+                        (TAINTED_RUN_START..TAINTED_RUN_START + TAINTED_RUN_LENGTH).contains(&i);
+                    if tainted && in_run {
+                        unsafe { taint_i32(byte as i32) as u8 }
+                    } else {
+                        byte
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn benchmark(blocks: usize) -> i32 {
+    let table = crc_table();
+    assert_eq!(crc32(&table, b"123456789"), 0xCBF43926);
+    assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+
+    let data = setup_blocks(blocks);
+    let mut tainted_blocks = 0;
+    for (block, bytes) in data.iter().enumerate() {
+        let crc = crc32(&table, bytes) as i32;
+        let adler = adler32(bytes) as i32;
+
+        if block.is_multiple_of(3) {
+            tainted_blocks += 1;
+            unsafe { assert_is_tainted_i32(adler) };
+        } else {
+            unsafe { assert_is_not_tainted_i32(adler) };
+        }
+        unsafe { assert_is_not_tainted_i32(crc) };
+    }
+
+    tainted_blocks
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
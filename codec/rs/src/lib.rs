@@ -0,0 +1,249 @@
+// Codec benchmark
+// Encodes a partially tainted buffer to Base64 and hex and decodes it again.
+// Characters are computed from their 6-bit (or 4-bit) group with branch-free
+// arithmetic instead of alphabet lookups, as constant-time codecs do, so the
+// taint of the input bits flows into the characters explicitly.
+//
+// A character is tainted iff its group includes bits of a tainted byte. Base64
+// groups straddle bytes, so decoded bytes are tainted iff they share a group
+// with a tainted byte, while hex round-trips the taint of every byte exactly.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const TAINTED_RUN: usize = 7;
+const TAINTED_PERIOD: usize = 5;
+const PADDING: u8 = b'=';
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+struct Input {
+    bytes: Vec<u8>,
+    // Whether each byte is tainted, for the checks.
+    tainted: Vec<bool>,
+}
+
+/// Generates random bytes, tainting one run of `TAINTED_RUN` bytes out of
+/// every `TAINTED_PERIOD`.
+fn setup_input(n: usize) -> Input {
+    let mut rng = Random::new();
+    let mut input = Input {
+        bytes: Vec::with_capacity(n),
+        tainted: Vec::with_capacity(n),
+    };
+    for i in 0..n {
+        let byte = rng.gen_random(256.0) as u8;
+        let tainted = (i / TAINTED_RUN).is_multiple_of(TAINTED_PERIOD);
+        input.bytes.push(if tainted {
+            unsafe { taint_i32(byte as i32) as u8 }
+        } else {
+            byte
+        });
+        input.tainted.push(tainted);
+    }
+    input
+}
+
+/// Maps `0..64` to `A-Z`, `a-z`, `0-9`, `+` and `/`.
+fn encode_base64_digit(value: u8) -> u8 {
+    let value = value as i32;
+    let mut diff = b'A' as i32;
+    // The shifts turn `bound - value` into an all-ones mask iff `value` is
+    // above `bound`.
+    diff += ((25 - value) >> 8) & 6;
+    diff -= ((51 - value) >> 8) & 75;
+    diff -= ((61 - value) >> 8) & 15;
+    diff += ((62 - value) >> 8) & 3;
+    (value + diff) as u8
+}
+
+/// Inverse of `encode_base64_digit`, returning -1 for invalid characters.
+fn decode_base64_digit(c: u8) -> i32 {
+    let c = c as i32;
+    let mut value = -1;
+    // Each term is masked to 0 unless `c` lies strictly between the bounds.
+    value += (((b'A' as i32 - 1 - c) & (c - b'Z' as i32 - 1)) >> 8) & (c - b'A' as i32 + 1);
+    value += (((b'a' as i32 - 1 - c) & (c - b'z' as i32 - 1)) >> 8) & (c - b'a' as i32 + 27);
+    value += (((b'0' as i32 - 1 - c) & (c - b'9' as i32 - 1)) >> 8) & (c - b'0' as i32 + 53);
+    value += (((b'+' as i32 - 1 - c) & (c - b'+' as i32 - 1)) >> 8) & 63;
+    value += (((b'/' as i32 - 1 - c) & (c - b'/' as i32 - 1)) >> 8) & 64;
+    value
+}
+
+fn encode_base64(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied().unwrap_or(0);
+        let b2 = chunk.get(2).copied().unwrap_or(0);
+
+        encoded.push(encode_base64_digit(b0 >> 2));
+        encoded.push(encode_base64_digit(((b0 & 0x03) << 4) | (b1 >> 4)));
+        encoded.push(if chunk.len() > 1 {
+            encode_base64_digit(((b1 & 0x0f) << 2) | (b2 >> 6))
+        } else {
+            PADDING
+        });
+        encoded.push(if chunk.len() > 2 {
+            encode_base64_digit(b2 & 0x3f)
+        } else {
+            PADDING
+        });
+    }
+    encoded
+}
+
+fn decode_base64(encoded: &[u8]) -> Option<Vec<u8>> {
+    if !encoded.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(encoded.len() / 4 * 3);
+    for chunk in encoded.chunks(4) {
+        let padding = chunk.iter().rev().take_while(|&&c| c == PADDING).count();
+        let mut group = 0;
+        for &c in &chunk[..4 - padding] {
+            let value = decode_base64_digit(c);
+            if value < 0 {
+                return None;
+            }
+            group = (group << 6) | value;
+        }
+        group <<= 6 * padding;
+
+        bytes.push((group >> 16) as u8);
+        if padding < 2 {
+            bytes.push((group >> 8) as u8);
+        }
+        if padding < 1 {
+            bytes.push(group as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Maps `0..16` to `0-9` and `a-f`.
+fn encode_hex_digit(value: u8) -> u8 {
+    let value = value as i32;
+    (value + b'0' as i32 + (((9 - value) >> 8) & (b'a' as i32 - b'0' as i32 - 10))) as u8
+}
+
+/// Inverse of `encode_hex_digit`, returning -1 for invalid characters.
+fn decode_hex_digit(c: u8) -> i32 {
+    let c = c as i32;
+    let mut value = -1;
+    value += (((b'0' as i32 - 1 - c) & (c - b'9' as i32 - 1)) >> 8) & (c - b'0' as i32 + 1);
+    value += (((b'a' as i32 - 1 - c) & (c - b'f' as i32 - 1)) >> 8) & (c - b'a' as i32 + 11);
+    value
+}
+
+fn encode_hex(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(bytes.len() * 2);
+    for &byte in bytes {
+        encoded.push(encode_hex_digit(byte >> 4));
+        encoded.push(encode_hex_digit(byte & 0x0f));
+    }
+    encoded
+}
+
+fn decode_hex(encoded: &[u8]) -> Option<Vec<u8>> {
+    if !encoded.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(encoded.len() / 2);
+    for pair in encoded.chunks(2) {
+        let high = decode_hex_digit(pair[0]);
+        let low = decode_hex_digit(pair[1]);
+        if high < 0 || low < 0 {
+            return None;
+        }
+        bytes.push(((high << 4) | low) as u8);
+    }
+    Some(bytes)
+}
+
+/// Returns, for each of `groups` `group_bits`-bit groups of a bit stream,
+/// whether it overlaps one of the tainted `unit_bits`-bit units of the stream.
+fn tainted_groups(
+    tainted: &[bool],
+    unit_bits: usize,
+    group_bits: usize,
+    groups: usize,
+) -> Vec<bool> {
+    (0..groups)
+        .map(|group| {
+            let first = group * group_bits / unit_bits;
+            let last = (((group + 1) * group_bits - 1) / unit_bits).min(tainted.len() - 1);
+            first <= last && tainted[first..=last].iter().any(|&t| t)
+        })
+        .collect()
+}
+
+fn assert_taint(bytes: &[u8], tainted: &[bool]) {
+    assert_eq!(bytes.len(), tainted.len());
+    for (&byte, &byte_tainted) in bytes.iter().zip(tainted) {
+        if byte_tainted {
+            unsafe { assert_is_tainted_i32(byte as i32) };
+        } else {
+            unsafe { assert_is_not_tainted_i32(byte as i32) };
+        }
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    assert_eq!(encode_base64(b"benchmark"), b"YmVuY2htYXJr");
+    assert_eq!(encode_base64(&[0xfb, 0xff]), b"+/8=");
+    assert_eq!(encode_hex(&[0x0f, 0xa9]), b"0fa9");
+
+    let input = setup_input(n);
+
+    let base64 = encode_base64(&input.bytes);
+    let base64_tainted = tainted_groups(&input.tainted, 8, 6, base64.len());
+    assert_taint(&base64, &base64_tainted);
+
+    // Decoding assembles each group of four characters into a single value, so
+    // the taint of a decoded byte covers its whole group of three bytes.
+    let decoded = decode_base64(&base64).unwrap();
+    assert_eq!(decoded, input.bytes);
+    let decoded_tainted: Vec<bool> = input
+        .tainted
+        .chunks(3)
+        .flat_map(|chunk| vec![chunk.iter().any(|&t| t); chunk.len()])
+        .collect();
+    assert_taint(&decoded, &decoded_tainted);
+
+    let hex = encode_hex(&input.bytes);
+    assert_taint(&hex, &tainted_groups(&input.tainted, 8, 4, hex.len()));
+    let decoded = decode_hex(&hex).unwrap();
+    assert_eq!(decoded, input.bytes);
+    assert_taint(&decoded, &input.tainted);
+
+    base64_tainted.iter().filter(|&&t| t).count() as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
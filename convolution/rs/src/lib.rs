@@ -0,0 +1,217 @@
+// Convolution benchmark
+// Applies a 5x5 Gaussian blur and then a 3x3 Sobel filter to a generated
+// grayscale `u8` image with a tainted rectangle. Every output pixel sums its
+// whole window, so the tainted rectangle grows by the kernel radius with each
+// filter. Image borders are handled by clamping coordinates into the image.
+//
+// Sobel magnitudes saturate at 255 through `min`, which selects between the
+// sum and the constant. A select yields the taint of the operand it picks, so
+// saturated pixels hold the untainted constant even within the tainted region.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const GAUSSIAN_RADIUS: usize = 2;
+const GAUSSIAN: [i32; 25] = [
+    1, 4, 6, 4, 1, //
+    4, 16, 24, 16, 4, //
+    6, 24, 36, 24, 6, //
+    4, 16, 24, 16, 4, //
+    1, 4, 6, 4, 1, //
+];
+const GAUSSIAN_SHIFT: i32 = 8;
+
+const SOBEL_RADIUS: usize = 1;
+const SOBEL_X: [i32; 9] = [
+    -1, 0, 1, //
+    -2, 0, 2, //
+    -1, 0, 1, //
+];
+const SOBEL_Y: [i32; 9] = [
+    -1, -2, -1, //
+    0, 0, 0, //
+    1, 2, 1, //
+];
+
+const CHECKER_SIZE: usize = 16;
+const NOISE: f64 = 32.0;
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Rect {
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+}
+
+impl Rect {
+    fn contains(&self, x: usize, y: usize) -> bool {
+        self.x0 <= x && x < self.x1 && self.y0 <= y && y < self.y1
+    }
+
+    fn grow(&self, radius: usize) -> Rect {
+        Rect {
+            x0: self.x0.saturating_sub(radius),
+            y0: self.y0.saturating_sub(radius),
+            x1: self.x1 + radius,
+            y1: self.y1 + radius,
+        }
+    }
+}
+
+struct Image {
+    pixels: Vec<u8>,
+    size: usize,
+}
+
+impl Image {
+    fn new(size: usize) -> Self {
+        Image {
+            pixels: vec![0; size * size],
+            size,
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.size + x]
+    }
+
+    fn set(&mut self, x: usize, y: usize, val: u8) {
+        self.pixels[y * self.size + x] = val;
+    }
+
+    /// Sums the window of `radius` around the pixel, weighted by `kernel`,
+    /// clamping coordinates that fall outside the image to its edges.
+    fn convolve(&self, x: usize, y: usize, kernel: &[i32], radius: usize) -> i32 {
+        let width = 2 * radius + 1;
+        let mut sum = 0;
+        for ky in 0..width {
+            let sy = (y + ky).saturating_sub(radius).min(self.size - 1);
+            for kx in 0..width {
+                let sx = (x + kx).saturating_sub(radius).min(self.size - 1);
+                sum += kernel[ky * width + kx] * self.get(sx, sy) as i32;
+            }
+        }
+        sum
+    }
+}
+
+/// Generates a noisy checkerboard, tainting the pixels within `tainted`.
+fn setup_image(size: usize, tainted: Rect) -> Image {
+    let mut rng = Random::new();
+    let mut image = Image::new(size);
+    for y in 0..size {
+        for x in 0..size {
+            let base = if (x / CHECKER_SIZE + y / CHECKER_SIZE).is_multiple_of(2) {
+                40
+            } else {
+                200
+            };
+            let pixel = base + rng.gen_random(NOISE) as i32;
+            let pixel = if tainted.contains(x, y) {
+                unsafe { taint_i32(pixel) }
+            } else {
+                pixel
+            };
+            image.set(x, y, pixel as u8);
+        }
+    }
+    image
+}
+
+fn gaussian_blur(image: &Image) -> Image {
+    let mut blurred = Image::new(image.size);
+    for y in 0..image.size {
+        for x in 0..image.size {
+            let sum = image.convolve(x, y, &GAUSSIAN, GAUSSIAN_RADIUS);
+            blurred.set(
+                x,
+                y,
+                ((sum + (1 << (GAUSSIAN_SHIFT - 1))) >> GAUSSIAN_SHIFT) as u8,
+            );
+        }
+    }
+    blurred
+}
+
+/// Returns the edge magnitudes, saturated at 255, and whether each pixel
+/// saturated.
+fn sobel(image: &Image) -> (Image, Vec<bool>) {
+    let mut edges = Image::new(image.size);
+    let mut saturated = vec![false; image.size * image.size];
+    for y in 0..image.size {
+        for x in 0..image.size {
+            let gx = image.convolve(x, y, &SOBEL_X, SOBEL_RADIUS);
+            let gy = image.convolve(x, y, &SOBEL_Y, SOBEL_RADIUS);
+            let magnitude = gx.abs() + gy.abs();
+            saturated[y * image.size + x] = magnitude > u8::MAX as i32;
+            edges.set(x, y, magnitude.min(u8::MAX as i32) as u8);
+        }
+    }
+    (edges, saturated)
+}
+
+/// Asserts that the pixels within `tainted` are tainted, except for those
+/// `constant` holds for, and that all others are not.
+fn check_region(image: &Image, tainted: Rect, constant: impl Fn(usize, usize) -> bool) {
+    for y in 0..image.size {
+        for x in 0..image.size {
+            let pixel = image.get(x, y) as i32;
+            if tainted.contains(x, y) && !constant(x, y) {
+                unsafe { assert_is_tainted_i32(pixel) };
+            } else {
+                unsafe { assert_is_not_tainted_i32(pixel) };
+            }
+        }
+    }
+}
+
+fn benchmark(size: usize) -> i32 {
+    let tainted = Rect {
+        x0: size / 4,
+        y0: size / 3,
+        x1: size / 2,
+        y1: 2 * size / 3,
+    };
+    let image = setup_image(size, tainted);
+    check_region(&image, tainted, |_, _| false);
+
+    let blurred = gaussian_blur(&image);
+    let blurred_tainted = tainted.grow(GAUSSIAN_RADIUS);
+    check_region(&blurred, blurred_tainted, |_, _| false);
+
+    let (edges, saturated) = sobel(&blurred);
+    check_region(&edges, blurred_tainted.grow(SOBEL_RADIUS), |x, y| {
+        saturated[y * size + x]
+    });
+
+    saturated.iter().filter(|&&s| s).count() as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
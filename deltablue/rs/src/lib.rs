@@ -0,0 +1,881 @@
+// DeltaBlue benchmark
+// Adapted from the Octane JavaScript version of the DeltaBlue incremental
+// constraint solver by John Maloney and Mario Wolczko. Variables are
+// `Rc<RefCell<Variable>>` and constraints `Rc<dyn Constraint>`, owned by the
+// planner. Constraints hold their variables, which refer back to them through
+// `Weak` references, and the planner dispatches dynamically on the constraint
+// kind. Some of the values given to input variables are tainted,
+// and the taint of the variables computed by the plans is checked after every
+// change.
+
+use std::cell::{Cell, RefCell};
+use std::ptr;
+use std::rc::{Rc, Weak};
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const CHAIN_EDITS: u32 = 100;
+const CHANGE_ITERATIONS: usize = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Strength {
+    Required,
+    StrongPreferred,
+    Preferred,
+    StrongDefault,
+    Normal,
+    WeakDefault,
+    Weakest,
+}
+
+impl Strength {
+    fn stronger(self, other: Strength) -> bool {
+        (self as i32) < (other as i32)
+    }
+
+    fn weaker(self, other: Strength) -> bool {
+        (self as i32) > (other as i32)
+    }
+
+    fn weakest_of(self, other: Strength) -> Strength {
+        if self.weaker(other) { self } else { other }
+    }
+
+    fn next_weaker(self) -> Strength {
+        match self {
+            Strength::Required => Strength::StrongPreferred,
+            Strength::StrongPreferred => Strength::Preferred,
+            Strength::Preferred => Strength::StrongDefault,
+            Strength::StrongDefault => Strength::Normal,
+            Strength::Normal => Strength::WeakDefault,
+            Strength::WeakDefault | Strength::Weakest => Strength::Weakest,
+        }
+    }
+}
+
+type VariableRef = Rc<RefCell<Variable>>;
+type ConstraintRef = Rc<dyn Constraint>;
+
+struct Variable {
+    value: i32,
+    constraints: Vec<Weak<dyn Constraint>>,
+    determined_by: Option<Weak<dyn Constraint>>,
+    mark: u32,
+    walk_strength: Strength,
+    stay: bool,
+}
+
+impl Variable {
+    fn new(value: i32) -> VariableRef {
+        Rc::new(RefCell::new(Variable {
+            value,
+            constraints: Vec::new(),
+            determined_by: None,
+            mark: 0,
+            walk_strength: Strength::Weakest,
+            stay: true,
+        }))
+    }
+
+    fn remove_constraint(&mut self, c: &ConstraintRef) {
+        self.constraints.retain(|other| !refers_to(other, c));
+        if self.is_determined_by(c) {
+            self.determined_by = None;
+        }
+    }
+
+    fn is_determined_by(&self, c: &ConstraintRef) -> bool {
+        self.determined_by.as_ref().is_some_and(|d| refers_to(d, c))
+    }
+
+    /// Returns the constraints on this variable and the one determining it.
+    fn constraints(&self) -> (Vec<ConstraintRef>, Option<ConstraintRef>) {
+        let constraints = self.constraints.iter().filter_map(Weak::upgrade).collect();
+        (
+            constraints,
+            self.determined_by.as_ref().and_then(Weak::upgrade),
+        )
+    }
+}
+
+fn refers_to(w: &Weak<dyn Constraint>, c: &ConstraintRef) -> bool {
+    ptr::addr_eq(w.as_ptr(), Rc::as_ptr(c))
+}
+
+trait Constraint {
+    fn strength(&self) -> Strength;
+    fn add_to_graph(&self, this: &ConstraintRef);
+    fn remove_from_graph(&self, this: &ConstraintRef);
+    fn choose_method(&self, mark: u32);
+    fn is_satisfied(&self) -> bool;
+    fn mark_inputs(&self, mark: u32);
+    fn output(&self) -> VariableRef;
+    fn recalculate(&self);
+    fn mark_unsatisfied(&self);
+    fn inputs_known(&self, mark: u32) -> bool;
+    fn execute(&self);
+
+    fn is_input(&self) -> bool {
+        false
+    }
+}
+
+/// State shared by constraints with a single output and no inputs.
+struct Unary {
+    my_output: VariableRef,
+    strength: Strength,
+    satisfied: Cell<bool>,
+}
+
+impl Unary {
+    fn new(v: &VariableRef, strength: Strength) -> Self {
+        Unary {
+            my_output: v.clone(),
+            strength,
+            satisfied: Cell::new(false),
+        }
+    }
+
+    fn add_to_graph(&self, this: &ConstraintRef) {
+        self.my_output
+            .borrow_mut()
+            .constraints
+            .push(Rc::downgrade(this));
+        self.satisfied.set(false);
+    }
+
+    fn remove_from_graph(&self, this: &ConstraintRef) {
+        self.my_output.borrow_mut().remove_constraint(this);
+        self.satisfied.set(false);
+    }
+
+    fn choose_method(&self, mark: u32) {
+        let output = self.my_output.borrow();
+        self.satisfied
+            .set(output.mark != mark && self.strength.stronger(output.walk_strength));
+    }
+
+    /// Returns whether the output stays, in which case it has to be computed.
+    fn recalculate(&self, is_input: bool) -> bool {
+        let mut output = self.my_output.borrow_mut();
+        output.walk_strength = self.strength;
+        output.stay = !is_input;
+        output.stay
+    }
+}
+
+/// Fixes a variable to its current value.
+struct StayConstraint {
+    unary: Unary,
+}
+
+/// Marks a variable as changed by the user.
+struct EditConstraint {
+    unary: Unary,
+}
+
+impl Constraint for StayConstraint {
+    fn strength(&self) -> Strength {
+        self.unary.strength
+    }
+
+    fn add_to_graph(&self, this: &ConstraintRef) {
+        self.unary.add_to_graph(this);
+    }
+
+    fn remove_from_graph(&self, this: &ConstraintRef) {
+        self.unary.remove_from_graph(this);
+    }
+
+    fn choose_method(&self, mark: u32) {
+        self.unary.choose_method(mark);
+    }
+
+    fn is_satisfied(&self) -> bool {
+        self.unary.satisfied.get()
+    }
+
+    fn mark_inputs(&self, _mark: u32) {}
+
+    fn output(&self) -> VariableRef {
+        self.unary.my_output.clone()
+    }
+
+    fn recalculate(&self) {
+        if self.unary.recalculate(self.is_input()) {
+            self.execute();
+        }
+    }
+
+    fn mark_unsatisfied(&self) {
+        self.unary.satisfied.set(false);
+    }
+
+    fn inputs_known(&self, _mark: u32) -> bool {
+        true
+    }
+
+    fn execute(&self) {}
+}
+
+impl Constraint for EditConstraint {
+    fn strength(&self) -> Strength {
+        self.unary.strength
+    }
+
+    fn add_to_graph(&self, this: &ConstraintRef) {
+        self.unary.add_to_graph(this);
+    }
+
+    fn remove_from_graph(&self, this: &ConstraintRef) {
+        self.unary.remove_from_graph(this);
+    }
+
+    fn choose_method(&self, mark: u32) {
+        self.unary.choose_method(mark);
+    }
+
+    fn is_satisfied(&self) -> bool {
+        self.unary.satisfied.get()
+    }
+
+    fn mark_inputs(&self, _mark: u32) {}
+
+    fn output(&self) -> VariableRef {
+        self.unary.my_output.clone()
+    }
+
+    fn recalculate(&self) {
+        if self.unary.recalculate(self.is_input()) {
+            self.execute();
+        }
+    }
+
+    fn mark_unsatisfied(&self) {
+        self.unary.satisfied.set(false);
+    }
+
+    fn inputs_known(&self, _mark: u32) -> bool {
+        true
+    }
+
+    fn execute(&self) {}
+
+    fn is_input(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    None,
+    Forward,
+    Backward,
+}
+
+/// State shared by constraints between two variables, which can be satisfied
+/// in either direction.
+struct Binary {
+    v1: VariableRef,
+    v2: VariableRef,
+    strength: Strength,
+    direction: Cell<Direction>,
+}
+
+impl Binary {
+    fn new(v1: &VariableRef, v2: &VariableRef, strength: Strength) -> Self {
+        Binary {
+            v1: v1.clone(),
+            v2: v2.clone(),
+            strength,
+            direction: Cell::new(Direction::None),
+        }
+    }
+
+    fn add_to_graph(&self, this: &ConstraintRef) {
+        self.v1.borrow_mut().constraints.push(Rc::downgrade(this));
+        self.v2.borrow_mut().constraints.push(Rc::downgrade(this));
+        self.direction.set(Direction::None);
+    }
+
+    fn remove_from_graph(&self, this: &ConstraintRef) {
+        self.v1.borrow_mut().remove_constraint(this);
+        self.v2.borrow_mut().remove_constraint(this);
+        self.direction.set(Direction::None);
+    }
+
+    /// As in the original, the last assignment decides the direction.
+    fn choose_method(&self, mark: u32) {
+        let v1 = self.v1.borrow();
+        let v2 = self.v2.borrow();
+        if v1.mark == mark {
+            self.direction.set(
+                if v2.mark != mark && self.strength.stronger(v2.walk_strength) {
+                    Direction::Forward
+                } else {
+                    Direction::None
+                },
+            );
+        }
+        if v2.mark == mark {
+            self.direction.set(
+                if v1.mark != mark && self.strength.stronger(v1.walk_strength) {
+                    Direction::Backward
+                } else {
+                    Direction::None
+                },
+            );
+        }
+        if v1.walk_strength.weaker(v2.walk_strength) {
+            self.direction
+                .set(if self.strength.stronger(v1.walk_strength) {
+                    Direction::Backward
+                } else {
+                    Direction::None
+                });
+        } else {
+            self.direction
+                .set(if self.strength.stronger(v2.walk_strength) {
+                    Direction::Forward
+                } else {
+                    Direction::Backward
+                });
+        }
+    }
+
+    fn is_satisfied(&self) -> bool {
+        self.direction.get() != Direction::None
+    }
+
+    fn input(&self) -> &VariableRef {
+        if self.direction.get() == Direction::Forward {
+            &self.v1
+        } else {
+            &self.v2
+        }
+    }
+
+    fn output(&self) -> &VariableRef {
+        if self.direction.get() == Direction::Forward {
+            &self.v2
+        } else {
+            &self.v1
+        }
+    }
+
+    fn mark_inputs(&self, mark: u32) {
+        self.input().borrow_mut().mark = mark;
+    }
+
+    fn inputs_known(&self, mark: u32) -> bool {
+        let input = self.input().borrow();
+        input.mark == mark || input.stay || input.determined_by.is_none()
+    }
+}
+
+/// Keeps two variables equal.
+struct EqualityConstraint {
+    binary: Binary,
+}
+
+impl Constraint for EqualityConstraint {
+    fn strength(&self) -> Strength {
+        self.binary.strength
+    }
+
+    fn add_to_graph(&self, this: &ConstraintRef) {
+        self.binary.add_to_graph(this);
+    }
+
+    fn remove_from_graph(&self, this: &ConstraintRef) {
+        self.binary.remove_from_graph(this);
+    }
+
+    fn choose_method(&self, mark: u32) {
+        self.binary.choose_method(mark);
+    }
+
+    fn is_satisfied(&self) -> bool {
+        self.binary.is_satisfied()
+    }
+
+    fn mark_inputs(&self, mark: u32) {
+        self.binary.mark_inputs(mark);
+    }
+
+    fn output(&self) -> VariableRef {
+        self.binary.output().clone()
+    }
+
+    fn recalculate(&self) {
+        let stay = {
+            let input = self.binary.input().borrow();
+            let mut output = self.binary.output().borrow_mut();
+            output.walk_strength = self.binary.strength.weakest_of(input.walk_strength);
+            output.stay = input.stay;
+            output.stay
+        };
+        if stay {
+            self.execute();
+        }
+    }
+
+    fn mark_unsatisfied(&self) {
+        self.binary.direction.set(Direction::None);
+    }
+
+    fn inputs_known(&self, mark: u32) -> bool {
+        self.binary.inputs_known(mark)
+    }
+
+    fn execute(&self) {
+        let value = self.binary.input().borrow().value;
+        self.binary.output().borrow_mut().value = value;
+    }
+}
+
+/// Keeps `v2 = v1 * scale + offset`.
+struct ScaleConstraint {
+    binary: Binary,
+    scale: VariableRef,
+    offset: VariableRef,
+}
+
+impl Constraint for ScaleConstraint {
+    fn strength(&self) -> Strength {
+        self.binary.strength
+    }
+
+    fn add_to_graph(&self, this: &ConstraintRef) {
+        self.binary.add_to_graph(this);
+        self.scale
+            .borrow_mut()
+            .constraints
+            .push(Rc::downgrade(this));
+        self.offset
+            .borrow_mut()
+            .constraints
+            .push(Rc::downgrade(this));
+    }
+
+    fn remove_from_graph(&self, this: &ConstraintRef) {
+        self.binary.remove_from_graph(this);
+        self.scale.borrow_mut().remove_constraint(this);
+        self.offset.borrow_mut().remove_constraint(this);
+    }
+
+    fn choose_method(&self, mark: u32) {
+        self.binary.choose_method(mark);
+    }
+
+    fn is_satisfied(&self) -> bool {
+        self.binary.is_satisfied()
+    }
+
+    fn mark_inputs(&self, mark: u32) {
+        self.binary.mark_inputs(mark);
+        self.scale.borrow_mut().mark = mark;
+        self.offset.borrow_mut().mark = mark;
+    }
+
+    fn output(&self) -> VariableRef {
+        self.binary.output().clone()
+    }
+
+    fn recalculate(&self) {
+        let stay = {
+            let input = self.binary.input().borrow();
+            let mut output = self.binary.output().borrow_mut();
+            output.walk_strength = self.binary.strength.weakest_of(input.walk_strength);
+            output.stay = input.stay && self.scale.borrow().stay && self.offset.borrow().stay;
+            output.stay
+        };
+        if stay {
+            self.execute();
+        }
+    }
+
+    fn mark_unsatisfied(&self) {
+        self.binary.direction.set(Direction::None);
+    }
+
+    fn inputs_known(&self, mark: u32) -> bool {
+        self.binary.inputs_known(mark)
+    }
+
+    fn execute(&self) {
+        let scale = self.scale.borrow().value;
+        let offset = self.offset.borrow().value;
+        if self.binary.direction.get() == Direction::Forward {
+            let value = self.binary.v1.borrow().value;
+            self.binary.v2.borrow_mut().value = value * scale + offset;
+        } else {
+            let value = self.binary.v2.borrow().value;
+            self.binary.v1.borrow_mut().value = (value - offset) / scale;
+        }
+    }
+}
+
+struct Plan {
+    constraints: Vec<ConstraintRef>,
+}
+
+impl Plan {
+    fn execute(&self) {
+        for c in &self.constraints {
+            c.execute();
+        }
+    }
+}
+
+struct Planner {
+    current_mark: u32,
+    // Every constraint in the graph, which variables only refer to weakly.
+    constraints: Vec<ConstraintRef>,
+}
+
+impl Planner {
+    fn new() -> Self {
+        Planner {
+            current_mark: 0,
+            constraints: Vec::new(),
+        }
+    }
+
+    fn new_mark(&mut self) -> u32 {
+        self.current_mark += 1;
+        self.current_mark
+    }
+
+    /// Adds `c` to the constraint graph and satisfies it if possible.
+    fn add_constraint(&mut self, c: ConstraintRef) -> ConstraintRef {
+        c.add_to_graph(&c);
+        self.constraints.push(c.clone());
+        self.incremental_add(&c);
+        c
+    }
+
+    /// Removes `c` from the constraint graph, satisfying what it was
+    /// overriding.
+    fn destroy_constraint(&mut self, c: &ConstraintRef) {
+        if c.is_satisfied() {
+            self.incremental_remove(c);
+        } else {
+            c.remove_from_graph(c);
+        }
+        self.constraints.retain(|other| !Rc::ptr_eq(other, c));
+    }
+
+    /// Satisfies `c` if it is stronger than what determines its output,
+    /// returning the constraint it overrides, if any.
+    fn satisfy(&mut self, c: &ConstraintRef, mark: u32) -> Option<ConstraintRef> {
+        c.choose_method(mark);
+        if !c.is_satisfied() {
+            assert!(
+                c.strength() != Strength::Required,
+                "Could not satisfy a required constraint"
+            );
+            return None;
+        }
+
+        c.mark_inputs(mark);
+        let output = c.output();
+        let overridden = output
+            .borrow_mut()
+            .determined_by
+            .take()
+            .and_then(|d| d.upgrade());
+        if let Some(overridden) = &overridden {
+            overridden.mark_unsatisfied();
+        }
+        output.borrow_mut().determined_by = Some(Rc::downgrade(c));
+        assert!(self.add_propagate(c, mark), "Cycle encountered");
+        output.borrow_mut().mark = mark;
+        overridden
+    }
+
+    fn incremental_add(&mut self, c: &ConstraintRef) {
+        let mark = self.new_mark();
+        let mut overridden = self.satisfy(c, mark);
+        while let Some(o) = overridden {
+            overridden = self.satisfy(&o, mark);
+        }
+    }
+
+    fn incremental_remove(&mut self, c: &ConstraintRef) {
+        let output = c.output();
+        c.mark_unsatisfied();
+        c.remove_from_graph(c);
+        let unsatisfied = self.remove_propagate_from(&output);
+        let mut strength = Strength::Required;
+        loop {
+            for u in &unsatisfied {
+                if u.strength() == strength {
+                    self.incremental_add(u);
+                }
+            }
+            strength = strength.next_weaker();
+            if strength == Strength::Weakest {
+                break;
+            }
+        }
+    }
+
+    /// Returns the plan computing the variables downstream of `sources`.
+    fn make_plan(&mut self, sources: Vec<ConstraintRef>) -> Plan {
+        let mark = self.new_mark();
+        let mut plan = Plan {
+            constraints: Vec::new(),
+        };
+        let mut todo = sources;
+        let mut next = 0;
+        while next < todo.len() {
+            let c = todo[next].clone();
+            next += 1;
+            let output = c.output();
+            if output.borrow().mark != mark && c.inputs_known(mark) {
+                plan.constraints.push(c);
+                output.borrow_mut().mark = mark;
+                add_constraints_consuming_to(&output, &mut todo);
+            }
+        }
+        plan
+    }
+
+    fn extract_plan_from_constraints(&mut self, constraints: &[ConstraintRef]) -> Plan {
+        let sources = constraints
+            .iter()
+            .filter(|c| c.is_input() && c.is_satisfied())
+            .cloned()
+            .collect();
+        self.make_plan(sources)
+    }
+
+    /// Recomputes the variables downstream of `c`, returning false if this
+    /// reaches a variable marked with `mark`, which is a cycle.
+    fn add_propagate(&mut self, c: &ConstraintRef, mark: u32) -> bool {
+        let mut todo = vec![c.clone()];
+        let mut next = 0;
+        while next < todo.len() {
+            let d = todo[next].clone();
+            next += 1;
+            if d.output().borrow().mark == mark {
+                self.incremental_remove(c);
+                return false;
+            }
+            d.recalculate();
+            add_constraints_consuming_to(&d.output(), &mut todo);
+        }
+        true
+    }
+
+    /// Clears the variables downstream of `output`, returning the unsatisfied
+    /// constraints found on the way.
+    fn remove_propagate_from(&mut self, output: &VariableRef) -> Vec<ConstraintRef> {
+        {
+            let mut output = output.borrow_mut();
+            output.determined_by = None;
+            output.walk_strength = Strength::Weakest;
+            output.stay = true;
+        }
+
+        let mut unsatisfied = Vec::new();
+        let mut todo = vec![output.clone()];
+        let mut next = 0;
+        while next < todo.len() {
+            let v = todo[next].clone();
+            next += 1;
+            let (constraints, determining) = v.borrow().constraints();
+            for c in &constraints {
+                if !c.is_satisfied() {
+                    unsatisfied.push(c.clone());
+                }
+            }
+            for c in &constraints {
+                let is_determining = determining.as_ref().is_some_and(|d| Rc::ptr_eq(d, c));
+                if !is_determining && c.is_satisfied() {
+                    c.recalculate();
+                    todo.push(c.output());
+                }
+            }
+        }
+        unsatisfied
+    }
+}
+
+fn add_constraints_consuming_to(v: &VariableRef, coll: &mut Vec<ConstraintRef>) {
+    let (constraints, determining) = v.borrow().constraints();
+    for c in constraints {
+        let is_determining = determining.as_ref().is_some_and(|d| Rc::ptr_eq(d, &c));
+        if !is_determining && c.is_satisfied() {
+            coll.push(c);
+        }
+    }
+}
+
+fn add_stay(planner: &mut Planner, v: &VariableRef, strength: Strength) -> ConstraintRef {
+    planner.add_constraint(Rc::new(StayConstraint {
+        unary: Unary::new(v, strength),
+    }))
+}
+
+fn add_edit(planner: &mut Planner, v: &VariableRef, strength: Strength) -> ConstraintRef {
+    planner.add_constraint(Rc::new(EditConstraint {
+        unary: Unary::new(v, strength),
+    }))
+}
+
+fn add_equality(planner: &mut Planner, v1: &VariableRef, v2: &VariableRef, strength: Strength) {
+    planner.add_constraint(Rc::new(EqualityConstraint {
+        binary: Binary::new(v1, v2, strength),
+    }));
+}
+
+fn add_scale(
+    planner: &mut Planner,
+    src: &VariableRef,
+    scale: &VariableRef,
+    offset: &VariableRef,
+    dst: &VariableRef,
+    strength: Strength,
+) {
+    planner.add_constraint(Rc::new(ScaleConstraint {
+        binary: Binary::new(src, dst, strength),
+        scale: scale.clone(),
+        offset: offset.clone(),
+    }));
+}
+
+/// Counts the asserted variables, checking their value and taint.
+struct Checker {
+    tainted: i32,
+}
+
+impl Checker {
+    fn check(&mut self, v: &VariableRef, expected: i32, tainted: bool) {
+        let value = v.borrow().value;
+        assert_eq!(value, expected);
+        if tainted {
+            unsafe { assert_is_tainted_i32(value) };
+            self.tainted += 1;
+        } else {
+            unsafe { assert_is_not_tainted_i32(value) };
+        }
+    }
+}
+
+/// Builds a chain of equality constraints and edits its first variable with
+/// values that are tainted on odd edits, which the whole chain follows.
+fn chain_test(n: usize, checker: &mut Checker) {
+    let mut planner = Planner::new();
+    let variables: Vec<VariableRef> = (0..=n).map(|_| Variable::new(0)).collect();
+    for pair in variables.windows(2) {
+        add_equality(&mut planner, &pair[0], &pair[1], Strength::Required);
+    }
+
+    let first = &variables[0];
+    let last = &variables[n];
+    add_stay(&mut planner, last, Strength::StrongDefault);
+    let edit = add_edit(&mut planner, first, Strength::Preferred);
+    let plan = planner.extract_plan_from_constraints(&[edit]);
+
+    for step in 0..CHAIN_EDITS {
+        let tainted = !step.is_multiple_of(2);
+        let i = step as i32;
+        first.borrow_mut().value = if tainted { unsafe { taint_i32(i) } } else { i };
+        plan.execute();
+        checker.check(last, i, tainted);
+    }
+    for v in &variables {
+        checker.check(v, CHAIN_EDITS as i32 - 1, true);
+    }
+}
+
+/// Edits `v` with `value`, executing the resulting plan.
+fn change(planner: &mut Planner, v: &VariableRef, value: i32) {
+    let edit = add_edit(planner, v, Strength::Preferred);
+    let plan = planner.extract_plan_from_constraints(std::slice::from_ref(&edit));
+    for _ in 0..CHANGE_ITERATIONS {
+        v.borrow_mut().value = value;
+        plan.execute();
+    }
+    planner.destroy_constraint(&edit);
+}
+
+/// Builds pairs of variables related by a shared scale and offset, with every
+/// third source tainted, and changes the scale, the offset and the last pair.
+fn projection_test(n: usize, checker: &mut Checker) {
+    let mut planner = Planner::new();
+    let scale = Variable::new(10);
+    let offset = Variable::new(1000);
+    let source_tainted = |i: usize| i.is_multiple_of(3);
+
+    let mut sources = Vec::with_capacity(n);
+    let mut dests = Vec::with_capacity(n);
+    for i in 0..n {
+        let value = i as i32;
+        let src = Variable::new(if source_tainted(i) {
+            unsafe { taint_i32(value) }
+        } else {
+            value
+        });
+        let dst = Variable::new(value);
+        add_stay(&mut planner, &src, Strength::Normal);
+        add_scale(
+            &mut planner,
+            &src,
+            &scale,
+            &offset,
+            &dst,
+            Strength::Required,
+        );
+        sources.push(src);
+        dests.push(dst);
+    }
+    for (i, dst) in dests.iter().enumerate() {
+        checker.check(dst, i as i32 * 10 + 1000, source_tainted(i));
+    }
+
+    let src = &sources[n - 1];
+    let dst = &dests[n - 1];
+    change(&mut planner, src, unsafe { taint_i32(17) });
+    checker.check(dst, 1170, true);
+
+    change(&mut planner, dst, 1050);
+    checker.check(src, 5, false);
+
+    change(&mut planner, &scale, 5);
+    for (i, dst) in dests.iter().enumerate().take(n - 1) {
+        checker.check(dst, i as i32 * 5 + 1000, source_tainted(i));
+    }
+    checker.check(dst, 5 * 5 + 1000, false);
+
+    change(&mut planner, &offset, unsafe { taint_i32(2000) });
+    for (i, dst) in dests.iter().enumerate().take(n - 1) {
+        checker.check(dst, i as i32 * 5 + 2000, true);
+    }
+    checker.check(dst, 5 * 5 + 2000, true);
+    for (i, src) in sources.iter().enumerate().take(n - 1) {
+        checker.check(src, i as i32, source_tainted(i));
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    let mut checker = Checker { tainted: 0 };
+    // Both tests edit the variables at the end of what they build.
+    if n > 0 {
+        chain_test(n, &mut checker);
+        projection_test(n, &mut checker);
+    }
+    checker.tainted
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
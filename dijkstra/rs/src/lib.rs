@@ -0,0 +1,191 @@
+// Dijkstra benchmark
+// Single-source shortest paths with a `BinaryHeap` priority queue over a
+// deterministic random graph, generated with the LCG used by fasta. A subset of
+// the edge weights is tainted, so a node's distance is tainted exactly when the
+// shortest path found to it crosses a tainted edge.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const EDGES_PER_NODE: usize = 4;
+const MAX_WEIGHT: f64 = 100.0;
+// Weight of the edges linking every node to the next, which keep the graph
+// strongly connected without being on many shortest paths.
+const RING_WEIGHT: i32 = 1000;
+const TAINTED_EDGE_PROBABILITY: f64 = 0.1;
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+struct Edge {
+    to: usize,
+    weight: i32,
+    // Whether the weight is tainted, for the checks.
+    tainted: bool,
+}
+
+struct Graph {
+    adjacency: Vec<Vec<Edge>>,
+}
+
+impl Graph {
+    fn random(n: usize) -> Self {
+        let mut rng = Random::new();
+        let mut adjacency: Vec<Vec<Edge>> = (0..n).map(|_| Vec::new()).collect();
+
+        for (from, edges) in adjacency.iter_mut().enumerate() {
+            edges.push(Edge {
+                to: (from + 1) % n,
+                weight: RING_WEIGHT,
+                tainted: false,
+            });
+            for _ in 0..EDGES_PER_NODE {
+                let to = rng.gen_random(n as f64) as usize;
+                let weight = 1 + rng.gen_random(MAX_WEIGHT) as i32;
+                let tainted = rng.gen_random(1.0) < TAINTED_EDGE_PROBABILITY;
+                edges.push(Edge {
+                    to,
+                    weight: if tainted {
+                        unsafe { taint_i32(weight) }
+                    } else {
+                        weight
+                    },
+                    tainted,
+                });
+            }
+        }
+
+        Graph { adjacency }
+    }
+}
+
+struct ShortestPaths {
+    dist: Vec<i32>,
+    // The edge each node was last relaxed through, as `(from, edge index)`.
+    pred: Vec<Option<(usize, usize)>>,
+}
+
+fn dijkstra(graph: &Graph, source: usize) -> ShortestPaths {
+    let n = graph.adjacency.len();
+    let mut dist = vec![i32::MAX; n];
+    let mut pred = vec![None; n];
+    let mut heap = BinaryHeap::new();
+
+    dist[source] = 0;
+    heap.push(Reverse((0, source)));
+
+    while let Some(Reverse((d, u))) = heap.pop() {
+        if d > dist[u] {
+            continue;
+        }
+        for (index, edge) in graph.adjacency[u].iter().enumerate() {
+            let candidate = d + edge.weight;
+            if candidate < dist[edge.to] {
+                dist[edge.to] = candidate;
+                pred[edge.to] = Some((u, index));
+                heap.push(Reverse((candidate, edge.to)));
+            }
+        }
+    }
+
+    ShortestPaths { dist, pred }
+}
+
+/// Returns, for every node, whether its shortest path crosses a tainted edge.
+fn tainted_paths(graph: &Graph, paths: &ShortestPaths) -> Vec<bool> {
+    let n = graph.adjacency.len();
+    let mut tainted: Vec<Option<bool>> = vec![None; n];
+
+    for start in 0..n {
+        // Walk up the predecessor tree until a node with a known answer.
+        let mut chain = Vec::new();
+        let mut node = start;
+        let mut crosses = loop {
+            if let Some(known) = tainted[node] {
+                break known;
+            }
+            match paths.pred[node] {
+                None => break false,
+                Some((from, _)) => {
+                    chain.push(node);
+                    node = from;
+                }
+            }
+        };
+        for &node in chain.iter().rev() {
+            let (from, index) = paths.pred[node].unwrap();
+            crosses |= graph.adjacency[from][index].tainted;
+            tainted[node] = Some(crosses);
+        }
+        tainted[start].get_or_insert(crosses);
+    }
+
+    tainted.into_iter().map(Option::unwrap).collect()
+}
+
+/// Checks the distances against a Bellman-Ford style fixpoint.
+fn check_distances(graph: &Graph, paths: &ShortestPaths) {
+    for (u, edges) in graph.adjacency.iter().enumerate() {
+        for edge in edges {
+            assert!(paths.dist[edge.to] <= paths.dist[u] + edge.weight);
+        }
+    }
+    for (v, pred) in paths.pred.iter().enumerate() {
+        if let Some((from, index)) = *pred {
+            assert_eq!(
+                paths.dist[v],
+                paths.dist[from] + graph.adjacency[from][index].weight
+            );
+        }
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    let graph = Graph::random(n);
+    if graph.adjacency.is_empty() {
+        return 0;
+    }
+    let paths = dijkstra(&graph, 0);
+    check_distances(&graph, &paths);
+
+    let tainted = tainted_paths(&graph, &paths);
+    let mut tainted_count = 0;
+    for (v, &crosses) in tainted.iter().enumerate() {
+        if crosses {
+            unsafe { assert_is_tainted_i32(paths.dist[v]) };
+            tainted_count += 1;
+        } else {
+            unsafe { assert_is_not_tainted_i32(paths.dist[v]) };
+        }
+    }
+
+    tainted_count
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
@@ -0,0 +1,358 @@
+// Expression benchmark
+// Lexes a source string of randomly generated arithmetic expressions and
+// evaluates each with a recursive-descent parser, looking identifiers up in a
+// symbol table of untainted constants. The digits of some literals are tainted,
+// and expressions nest parentheses deeply, so tainted intermediate values are
+// held across many levels of recursion.
+//
+// Literal values are computed from their digits, and every operator is
+// arithmetic, so a result is tainted iff its expression references a tainted
+// literal. Operators and identifiers are never tainted.
+
+use std::collections::HashMap;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i64(val: i64);
+    fn assert_is_not_tainted_i64(val: i64);
+}
+
+const MAX_DEPTH: usize = 6;
+const MAX_NESTING: usize = 64;
+const TAINTED_LITERAL_PROBABILITY: f64 = 0.05;
+const SYMBOLS: [(&str, i64); 5] = [
+    ("width", 640),
+    ("height", 480),
+    ("depth", 24),
+    ("scale", 3),
+    ("offset", -7),
+];
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Token<'a> {
+    Number(i64),
+    Identifier(&'a [u8]),
+    Operator(u8),
+    LeftParen,
+    RightParen,
+    Semicolon,
+}
+
+fn tokenize(source: &[u8]) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < source.len() {
+        let c = source[i];
+        match c {
+            b' ' | b'\t' | b'\n' => i += 1,
+            b'0'..=b'9' => {
+                let mut value: i64 = 0;
+                while i < source.len() && source[i].is_ascii_digit() {
+                    value = value
+                        .wrapping_mul(10)
+                        .wrapping_add((source[i] - b'0') as i64);
+                    i += 1;
+                }
+                tokens.push(Token::Number(value));
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                let start = i;
+                while i < source.len() && (source[i].is_ascii_alphanumeric() || source[i] == b'_') {
+                    i += 1;
+                }
+                tokens.push(Token::Identifier(&source[start..i]));
+            }
+            b'+' | b'-' | b'*' | b'/' | b'%' => {
+                tokens.push(Token::Operator(c));
+                i += 1;
+            }
+            b'(' => {
+                tokens.push(Token::LeftParen);
+                i += 1;
+            }
+            b')' => {
+                tokens.push(Token::RightParen);
+                i += 1;
+            }
+            b';' => {
+                tokens.push(Token::Semicolon);
+                i += 1;
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+/// Evaluates the grammar
+///
+/// ```text
+/// statement  = expression ";"
+/// expression = term (("+" | "-") term)*
+/// term       = factor (("*" | "/" | "%") factor)*
+/// factor     = number | identifier | "-" factor | "(" expression ")"
+/// ```
+///
+/// with wrapping arithmetic, failing on division by zero.
+struct Parser<'a> {
+    tokens: &'a [Token<'a>],
+    pos: usize,
+    symbols: &'a HashMap<&'static [u8], i64>,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = *self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    /// Consumes the next token if it is one of `operators`.
+    fn operator(&mut self, operators: &[u8]) -> Option<u8> {
+        match self.tokens.get(self.pos) {
+            Some(&Token::Operator(op)) if operators.contains(&op) => {
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn statement(&mut self) -> Option<i64> {
+        let value = self.expression()?;
+        match self.next()? {
+            Token::Semicolon => Some(value),
+            _ => None,
+        }
+    }
+
+    fn expression(&mut self) -> Option<i64> {
+        let mut value = self.term()?;
+        while let Some(op) = self.operator(b"+-") {
+            let rhs = self.term()?;
+            value = if op == b'+' {
+                value.wrapping_add(rhs)
+            } else {
+                value.wrapping_sub(rhs)
+            };
+        }
+        Some(value)
+    }
+
+    fn term(&mut self) -> Option<i64> {
+        let mut value = self.factor()?;
+        while let Some(op) = self.operator(b"*/%") {
+            let rhs = self.factor()?;
+            value = match op {
+                b'*' => value.wrapping_mul(rhs),
+                _ if rhs == 0 => return None,
+                b'/' => value.wrapping_div(rhs),
+                _ => value.wrapping_rem(rhs),
+            };
+        }
+        Some(value)
+    }
+
+    fn factor(&mut self) -> Option<i64> {
+        match self.next()? {
+            Token::Number(value) => Some(value),
+            Token::Identifier(name) => self.symbols.get(name).copied(),
+            Token::Operator(b'-') => Some(self.factor()?.wrapping_neg()),
+            Token::LeftParen => {
+                let value = self.expression()?;
+                match self.next()? {
+                    Token::RightParen => Some(value),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Generated source text, with its value and whether it references a tainted
+/// literal, for the checks.
+struct Expression {
+    source: Vec<u8>,
+    value: i64,
+    tainted: bool,
+}
+
+/// Generates expressions following the grammar evaluated by `Parser`.
+struct Generator {
+    rng: Random,
+}
+
+impl Generator {
+    fn expression(&mut self, depth: usize) -> Expression {
+        let mut expression = self.term(depth);
+        while self.rng.gen_random(1.0) < 0.4 {
+            let rhs = self.term(depth);
+            let op = if self.rng.gen_random(1.0) < 0.5 {
+                b'+'
+            } else {
+                b'-'
+            };
+            let value = if op == b'+' {
+                expression.value.wrapping_add(rhs.value)
+            } else {
+                expression.value.wrapping_sub(rhs.value)
+            };
+            expression = Generator::binary(expression, op, rhs, value);
+        }
+        expression
+    }
+
+    fn term(&mut self, depth: usize) -> Expression {
+        let mut term = self.factor(depth);
+        while self.rng.gen_random(1.0) < 0.4 {
+            let rhs = self.factor(depth);
+            let choice = self.rng.gen_random(3.0) as usize;
+            let (op, value) = match choice {
+                1 if rhs.value != 0 => (b'/', term.value.wrapping_div(rhs.value)),
+                2 if rhs.value != 0 => (b'%', term.value.wrapping_rem(rhs.value)),
+                _ => (b'*', term.value.wrapping_mul(rhs.value)),
+            };
+            term = Generator::binary(term, op, rhs, value);
+        }
+        term
+    }
+
+    fn factor(&mut self, depth: usize) -> Expression {
+        let choice = if depth == 0 {
+            0.0
+        } else {
+            self.rng.gen_random(1.0)
+        };
+        if choice < 0.4 {
+            self.literal()
+        } else if choice < 0.55 {
+            let (name, value) = SYMBOLS[self.rng.gen_random(SYMBOLS.len() as f64) as usize];
+            Expression {
+                source: name.as_bytes().to_vec(),
+                value,
+                tainted: false,
+            }
+        } else if choice < 0.65 {
+            let mut factor = self.factor(depth - 1);
+            factor.source.insert(0, b'-');
+            factor.value = factor.value.wrapping_neg();
+            factor
+        } else {
+            let nesting = if choice < 0.7 {
+                1 + self.rng.gen_random(MAX_NESTING as f64) as usize
+            } else {
+                1
+            };
+            let inner = self.expression(depth - 1);
+            let mut source = vec![b'('; nesting];
+            source.extend_from_slice(&inner.source);
+            source.resize(source.len() + nesting, b')');
+            Expression { source, ..inner }
+        }
+    }
+
+    fn literal(&mut self) -> Expression {
+        let value = self.rng.gen_random(1000.0) as i64;
+        let tainted = self.rng.gen_random(1.0) < TAINTED_LITERAL_PROBABILITY;
+        let source = value
+            .to_string()
+            .into_bytes()
+            .into_iter()
+            .map(|digit| {
+                if tainted {
+                    unsafe { taint_i32(digit as i32) as u8 }
+                } else {
+                    digit
+                }
+            })
+            .collect();
+        Expression {
+            source,
+            value,
+            tainted,
+        }
+    }
+
+    fn binary(lhs: Expression, op: u8, rhs: Expression, value: i64) -> Expression {
+        let mut source = lhs.source;
+        source.extend_from_slice(&[b' ', op, b' ']);
+        source.extend_from_slice(&rhs.source);
+        Expression {
+            source,
+            value,
+            tainted: lhs.tainted || rhs.tainted,
+        }
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    let symbols: HashMap<&'static [u8], i64> = SYMBOLS
+        .iter()
+        .map(|&(name, value)| (name.as_bytes(), value))
+        .collect();
+
+    let tokens = tokenize(b"-(width - 2 * offset) % scale;").unwrap();
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        symbols: &symbols,
+    };
+    assert_eq!(parser.statement(), Some(-654 % 3));
+
+    let mut generator = Generator { rng: Random::new() };
+    let mut source = Vec::new();
+    let mut expressions = Vec::with_capacity(n);
+    for _ in 0..n {
+        let expression = generator.expression(MAX_DEPTH);
+        source.extend_from_slice(&expression.source);
+        source.extend_from_slice(b";\n");
+        expressions.push((expression.value, expression.tainted));
+    }
+
+    let tokens = tokenize(&source).unwrap();
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        symbols: &symbols,
+    };
+    let mut tainted_results = 0;
+    for (expected, tainted) in expressions {
+        let value = parser.statement().unwrap();
+        assert_eq!(value, expected);
+        if tainted {
+            unsafe { assert_is_tainted_i64(value) };
+            tainted_results += 1;
+        } else {
+            unsafe { assert_is_not_tainted_i64(value) };
+        }
+    }
+    assert_eq!(parser.pos, tokens.len());
+
+    tainted_results
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
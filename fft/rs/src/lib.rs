@@ -0,0 +1,290 @@
+// FFT benchmark
+// Radix-2 Cooley-Tukey FFT over a signal with a single tainted sample, run once
+// on `f64` samples and once on `i32` Q15 fixed-point samples. Both share the
+// same generic implementation, which halves after every butterfly so that the
+// fixed-point version cannot overflow, and hence have identical dataflow.
+//
+// Every output bin depends on every input sample, so all bins are tainted,
+// while the twiddle tables are only read and stay clean.
+
+use std::f64::consts::PI;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn taint_f64(val: f64) -> f64;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_tainted_f64(val: f64);
+    fn assert_is_not_tainted_i32(val: i32);
+    fn assert_is_not_tainted_f64(val: f64);
+}
+
+const Q15_SHIFT: u32 = 15;
+const Q15_ONE: f64 = (1 << Q15_SHIFT) as f64;
+const SINE_BIN: usize = 5;
+const SINE_AMPLITUDE: f64 = 0.5;
+const COSINE_BIN: usize = 37;
+const COSINE_AMPLITUDE: f64 = 0.25;
+const TOLERANCE: f64 = 1e-3;
+
+trait Sample: Copy {
+    fn from_f64(x: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn add(self, other: Self) -> Self;
+    fn sub(self, other: Self) -> Self;
+    fn mul(self, other: Self) -> Self;
+    fn half(self) -> Self;
+    fn taint(self) -> Self;
+    fn assert_is_tainted(self);
+    fn assert_is_not_tainted(self);
+}
+
+impl Sample for f64 {
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn add(self, other: Self) -> Self {
+        self + other
+    }
+
+    fn sub(self, other: Self) -> Self {
+        self - other
+    }
+
+    fn mul(self, other: Self) -> Self {
+        self * other
+    }
+
+    fn half(self) -> Self {
+        self * 0.5
+    }
+
+    fn taint(self) -> Self {
+        unsafe { taint_f64(self) }
+    }
+
+    fn assert_is_tainted(self) {
+        unsafe { assert_is_tainted_f64(self) };
+    }
+
+    fn assert_is_not_tainted(self) {
+        unsafe { assert_is_not_tainted_f64(self) };
+    }
+}
+
+/// Fixed-point number with 15 fractional bits.
+#[derive(Clone, Copy)]
+struct Q15(i32);
+
+impl Sample for Q15 {
+    fn from_f64(x: f64) -> Self {
+        Q15((x * Q15_ONE).round() as i32)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.0 as f64 / Q15_ONE
+    }
+
+    fn add(self, other: Self) -> Self {
+        Q15(self.0 + other.0)
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Q15(self.0 - other.0)
+    }
+
+    fn mul(self, other: Self) -> Self {
+        let product = self.0 as i64 * other.0 as i64;
+        Q15(((product + (1 << (Q15_SHIFT - 1))) >> Q15_SHIFT) as i32)
+    }
+
+    fn half(self) -> Self {
+        Q15(self.0 >> 1)
+    }
+
+    fn taint(self) -> Self {
+        Q15(unsafe { taint_i32(self.0) })
+    }
+
+    fn assert_is_tainted(self) {
+        unsafe { assert_is_tainted_i32(self.0) };
+    }
+
+    fn assert_is_not_tainted(self) {
+        unsafe { assert_is_not_tainted_i32(self.0) };
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Complex<T> {
+    re: T,
+    im: T,
+}
+
+impl<T: Sample> Complex<T> {
+    fn add(self, other: Self) -> Self {
+        Complex {
+            re: self.re.add(other.re),
+            im: self.im.add(other.im),
+        }
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Complex {
+            re: self.re.sub(other.re),
+            im: self.im.sub(other.im),
+        }
+    }
+
+    fn mul(self, other: Self) -> Self {
+        Complex {
+            re: self.re.mul(other.re).sub(self.im.mul(other.im)),
+            im: self.re.mul(other.im).add(self.im.mul(other.re)),
+        }
+    }
+
+    fn half(self) -> Self {
+        Complex {
+            re: self.re.half(),
+            im: self.im.half(),
+        }
+    }
+}
+
+/// Returns `exp(-2 pi i k / n)` for `k` in `0..n / 2`.
+fn twiddles<T: Sample>(n: usize) -> Vec<Complex<T>> {
+    (0..n / 2)
+        .map(|k| {
+            let angle = -2.0 * PI * k as f64 / n as f64;
+            Complex {
+                re: T::from_f64(angle.cos()),
+                im: T::from_f64(angle.sin()),
+            }
+        })
+        .collect()
+}
+
+/// Returns the signal, with the sample at `n / 3` tainted.
+fn signal<T: Sample>(n: usize) -> Vec<Complex<T>> {
+    (0..n)
+        .map(|i| {
+            let t = 2.0 * PI * i as f64 / n as f64;
+            let x = SINE_AMPLITUDE * (SINE_BIN as f64 * t).sin()
+                + COSINE_AMPLITUDE * (COSINE_BIN as f64 * t).cos();
+            let re = T::from_f64(x);
+            Complex {
+                re: if i == n / 3 { re.taint() } else { re },
+                im: T::from_f64(0.0),
+            }
+        })
+        .collect()
+}
+
+fn bit_reverse<T>(data: &mut [T]) {
+    let bits = data.len().trailing_zeros();
+    for i in 0..data.len() {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+}
+
+/// Computes the FFT of `data` in place, scaled by `1 / data.len()`.
+fn fft<T: Sample>(data: &mut [Complex<T>], twiddles: &[Complex<T>]) {
+    let n = data.len();
+    bit_reverse(data);
+
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let step = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..half {
+                let a = data[start + k];
+                let b = data[start + k + half].mul(twiddles[k * step]);
+                data[start + k] = a.add(b).half();
+                data[start + k + half] = a.sub(b).half();
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// Runs the FFT, checks the taint of the bins and twiddles and returns the
+/// bins as `f64`.
+fn run<T: Sample>(n: usize) -> Vec<Complex<f64>> {
+    let twiddles = twiddles::<T>(n);
+    let mut data = signal::<T>(n);
+    fft(&mut data, &twiddles);
+
+    for bin in &data {
+        bin.re.assert_is_tainted();
+        bin.im.assert_is_tainted();
+    }
+    for twiddle in &twiddles {
+        twiddle.re.assert_is_not_tainted();
+        twiddle.im.assert_is_not_tainted();
+    }
+
+    data.iter()
+        .map(|bin| Complex {
+            re: bin.re.to_f64(),
+            im: bin.im.to_f64(),
+        })
+        .collect()
+}
+
+fn check_spectrum(bins: &[Complex<f64>]) {
+    let n = bins.len();
+    for (k, bin) in bins.iter().enumerate() {
+        // A real sine of amplitude a shows up as -a/2 i in its bin and a/2 i in
+        // the mirrored one, a cosine as a/2 in both.
+        let (re, im) = match k {
+            SINE_BIN => (0.0, -SINE_AMPLITUDE / 2.0),
+            k if k == n - SINE_BIN => (0.0, SINE_AMPLITUDE / 2.0),
+            COSINE_BIN => (COSINE_AMPLITUDE / 2.0, 0.0),
+            k if k == n - COSINE_BIN => (COSINE_AMPLITUDE / 2.0, 0.0),
+            _ => (0.0, 0.0),
+        };
+        assert!((bin.re - re).abs() < TOLERANCE);
+        assert!((bin.im - im).abs() < TOLERANCE);
+    }
+}
+
+/// Rounds `n` up to a power of two with room for both frequencies below the
+/// Nyquist bin, and returns the summed magnitudes of the fixed-point bins in
+/// Q15 units, which include its rounding errors.
+fn benchmark(n: usize) -> i32 {
+    let n = n.max(2 * COSINE_BIN + 1).next_power_of_two();
+
+    let float_bins = run::<f64>(n);
+    check_spectrum(&float_bins);
+
+    let fixed_bins = run::<Q15>(n);
+    check_spectrum(&fixed_bins);
+
+    let mut peak = 0;
+    for (k, bin) in fixed_bins.iter().enumerate().take(n / 2) {
+        let magnitude = bin.re.abs() + bin.im.abs();
+        if magnitude > fixed_bins[peak].re.abs() + fixed_bins[peak].im.abs() {
+            peak = k;
+        }
+    }
+    assert_eq!(peak, SINE_BIN);
+
+    fixed_bins
+        .iter()
+        .map(|bin| ((bin.re.abs() + bin.im.abs()) * Q15_ONE) as i32)
+        .sum()
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
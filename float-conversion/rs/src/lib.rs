@@ -0,0 +1,479 @@
+// Float conversion benchmark
+// Round-trips partially tainted f64 values through strings, with `to_string`
+// and `parse::<f64>()` from the standard library and with a hand-written
+// printer producing the shortest digits that parse back to the same value.
+//
+// The printer splits `f64::to_bits` into an integer mantissa and exponent,
+// scales them to an exact fixed-point number, and generates digits by
+// multiplying the fraction by ten, as Grisu does without the approximation.
+// Every digit is computed from the mantissa, so it is tainted iff the value
+// is, and parsing the digits back yields a tainted value again.
+//
+// `to_string` generates digits with Grisu, which works on approximations of
+// the value and gives up on the few values where they cannot decide a digit,
+// leaving them to Dragon4, which selects each digit by comparisons. A port of
+// Grisu tells these values apart: the digits of `to_string` are tainted iff
+// Grisu generates them, the zeros and the point around them never are, and
+// the value parsed back is tainted iff the digits are. `benchmark` returns the
+// number of tainted values left to Dragon4.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_f64(val: f64) -> f64;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_tainted_f64(val: f64);
+    fn assert_is_not_tainted_i32(val: i32);
+    fn assert_is_not_tainted_f64(val: f64);
+}
+
+const TAINTED_PERIOD: usize = 2;
+const FRACTION_BITS: u32 = 52;
+const EXPONENT_BIAS: i32 = 1023;
+// The printer handles magnitudes in `[2^-20, 2^52)`, whose ulp is below one
+// and whose fraction fits a `u128` with room for multiplying it by ten.
+const MIN_MAGNITUDE: f64 = 1.0 / (1u64 << 20) as f64;
+const MAX_MAGNITUDE: f64 = (1u64 << FRACTION_BITS) as f64;
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+/// Splits a positive normal `x` into `(mantissa, exponent, biased_exponent)`,
+/// with `x = mantissa * 2^exponent`.
+fn decompose(x: f64) -> (u64, i32, i32) {
+    let bits = x.to_bits();
+    let biased_exponent = (bits >> FRACTION_BITS) as i32 & 0x7ff;
+    let fraction = bits & ((1 << FRACTION_BITS) - 1);
+    let mantissa = fraction | (1 << FRACTION_BITS);
+    let exponent = biased_exponent - EXPONENT_BIAS - FRACTION_BITS as i32;
+    (mantissa, exponent, biased_exponent)
+}
+
+/// Decides whether to stop generating digits when `remainder` is left below
+/// the current digit, returning whether to round it up. The value can be
+/// rounded down (up) when it stays closer to `x` than its lower (upper)
+/// neighbour, that is, within `low` (`high`) of `x`.
+fn terminate(remainder: u128, low: u128, high: u128, one: u128) -> Option<bool> {
+    let down = remainder < low;
+    let up = remainder + high > one;
+    match (down, up) {
+        (false, false) => None,
+        (true, false) => Some(false),
+        (false, true) => Some(true),
+        (true, true) => Some(2 * remainder >= one),
+    }
+}
+
+fn write_integer(mut integer: u64, out: &mut Vec<u8>) {
+    let start = out.len();
+    loop {
+        out.push(b'0' + (integer % 10) as u8);
+        integer /= 10;
+        if integer == 0 {
+            break;
+        }
+    }
+    out[start..].reverse();
+}
+
+/// Writes the shortest decimal representation of `x` that parses back to `x`,
+/// in the format of `f64::to_string`.
+fn write_shortest(x: f64, out: &mut Vec<u8>) {
+    assert!((MIN_MAGNITUDE..MAX_MAGNITUDE).contains(&x.abs()));
+    if x.is_sign_negative() {
+        out.push(b'-');
+    }
+
+    let (mantissa, exponent, biased_exponent) = decompose(x.abs());
+    // Fixed point with two more fraction bits than `x`, so that the distances
+    // to the midpoints between `x` and its neighbours are integers. The lower
+    // neighbour is twice as close when `x` is a power of two.
+    let shift = (2 - exponent) as u32;
+    let one = 1u128 << shift;
+    let scaled = (mantissa as u128) << 2;
+    let mut high = 2;
+    let mut low = if mantissa == 1 << FRACTION_BITS && biased_exponent > 1 {
+        1
+    } else {
+        2
+    };
+
+    let integer = (scaled >> shift) as u64;
+    let mut remainder = scaled & (one - 1);
+    if let Some(up) = terminate(remainder, low, high, one) {
+        write_integer(integer + up as u64, out);
+        return;
+    }
+    write_integer(integer, out);
+    out.push(b'.');
+    loop {
+        remainder *= 10;
+        low *= 10;
+        high *= 10;
+        let digit = (remainder >> shift) as u8;
+        remainder &= one - 1;
+        match terminate(remainder, low, high, one) {
+            None => out.push(b'0' + digit),
+            Some(up) => {
+                out.push(b'0' + digit + up as u8);
+                break;
+            }
+        }
+    }
+}
+
+/// Binary floating-point number `f * 2^e` with a 64-bit mantissa.
+#[derive(Clone, Copy)]
+struct Fp {
+    f: u64,
+    e: i16,
+}
+
+impl Fp {
+    /// Multiplies two numbers, rounding the mantissa of the product.
+    fn mul(self, other: Fp) -> Fp {
+        let product = self.f as u128 * other.f as u128;
+        Fp {
+            f: (product >> 64) as u64 + ((product as u64) >> 63),
+            e: self.e + other.e + 64,
+        }
+    }
+
+    fn normalize(self) -> Fp {
+        let zeros = self.f.leading_zeros();
+        Fp {
+            f: self.f << zeros,
+            e: self.e - zeros as i16,
+        }
+    }
+
+    fn normalize_to(self, e: i16) -> Fp {
+        Fp {
+            f: self.f << (self.e - e),
+            e,
+        }
+    }
+}
+
+// Powers of ten `(f, e, k)` with `f * 2^e` approximating `10^k`, every eighth
+// one from `10^-308`, as cached by Grisu in `core`.
+const CACHED_POW10: [(u64, i16, i16); 81] = [
+    (0xe61acf033d1a45df, -1087, -308),
+    (0xab70fe17c79ac6ca, -1060, -300),
+    (0xff77b1fcbebcdc4f, -1034, -292),
+    (0xbe5691ef416bd60c, -1007, -284),
+    (0x8dd01fad907ffc3c, -980, -276),
+    (0xd3515c2831559a83, -954, -268),
+    (0x9d71ac8fada6c9b5, -927, -260),
+    (0xea9c227723ee8bcb, -901, -252),
+    (0xaecc49914078536d, -874, -244),
+    (0x823c12795db6ce57, -847, -236),
+    (0xc21094364dfb5637, -821, -228),
+    (0x9096ea6f3848984f, -794, -220),
+    (0xd77485cb25823ac7, -768, -212),
+    (0xa086cfcd97bf97f4, -741, -204),
+    (0xef340a98172aace5, -715, -196),
+    (0xb23867fb2a35b28e, -688, -188),
+    (0x84c8d4dfd2c63f3b, -661, -180),
+    (0xc5dd44271ad3cdba, -635, -172),
+    (0x936b9fcebb25c996, -608, -164),
+    (0xdbac6c247d62a584, -582, -156),
+    (0xa3ab66580d5fdaf6, -555, -148),
+    (0xf3e2f893dec3f126, -529, -140),
+    (0xb5b5ada8aaff80b8, -502, -132),
+    (0x87625f056c7c4a8b, -475, -124),
+    (0xc9bcff6034c13053, -449, -116),
+    (0x964e858c91ba2655, -422, -108),
+    (0xdff9772470297ebd, -396, -100),
+    (0xa6dfbd9fb8e5b88f, -369, -92),
+    (0xf8a95fcf88747d94, -343, -84),
+    (0xb94470938fa89bcf, -316, -76),
+    (0x8a08f0f8bf0f156b, -289, -68),
+    (0xcdb02555653131b6, -263, -60),
+    (0x993fe2c6d07b7fac, -236, -52),
+    (0xe45c10c42a2b3b06, -210, -44),
+    (0xaa242499697392d3, -183, -36),
+    (0xfd87b5f28300ca0e, -157, -28),
+    (0xbce5086492111aeb, -130, -20),
+    (0x8cbccc096f5088cc, -103, -12),
+    (0xd1b71758e219652c, -77, -4),
+    (0x9c40000000000000, -50, 4),
+    (0xe8d4a51000000000, -24, 12),
+    (0xad78ebc5ac620000, 3, 20),
+    (0x813f3978f8940984, 30, 28),
+    (0xc097ce7bc90715b3, 56, 36),
+    (0x8f7e32ce7bea5c70, 83, 44),
+    (0xd5d238a4abe98068, 109, 52),
+    (0x9f4f2726179a2245, 136, 60),
+    (0xed63a231d4c4fb27, 162, 68),
+    (0xb0de65388cc8ada8, 189, 76),
+    (0x83c7088e1aab65db, 216, 84),
+    (0xc45d1df942711d9a, 242, 92),
+    (0x924d692ca61be758, 269, 100),
+    (0xda01ee641a708dea, 295, 108),
+    (0xa26da3999aef774a, 322, 116),
+    (0xf209787bb47d6b85, 348, 124),
+    (0xb454e4a179dd1877, 375, 132),
+    (0x865b86925b9bc5c2, 402, 140),
+    (0xc83553c5c8965d3d, 428, 148),
+    (0x952ab45cfa97a0b3, 455, 156),
+    (0xde469fbd99a05fe3, 481, 164),
+    (0xa59bc234db398c25, 508, 172),
+    (0xf6c69a72a3989f5c, 534, 180),
+    (0xb7dcbf5354e9bece, 561, 188),
+    (0x88fcf317f22241e2, 588, 196),
+    (0xcc20ce9bd35c78a5, 614, 204),
+    (0x98165af37b2153df, 641, 212),
+    (0xe2a0b5dc971f303a, 667, 220),
+    (0xa8d9d1535ce3b396, 694, 228),
+    (0xfb9b7cd9a4a7443c, 720, 236),
+    (0xbb764c4ca7a44410, 747, 244),
+    (0x8bab8eefb6409c1a, 774, 252),
+    (0xd01fef10a657842c, 800, 260),
+    (0x9b10a4e5e9913129, 827, 268),
+    (0xe7109bfba19c0c9d, 853, 276),
+    (0xac2820d9623bf429, 880, 284),
+    (0x80444b5e7aa7cf85, 907, 292),
+    (0xbf21e44003acdd2d, 933, 300),
+    (0x8e679c2f5e44ff8f, 960, 308),
+    (0xd433179d9c8cb841, 986, 316),
+    (0x9e19db92b4e31ba9, 1013, 324),
+    (0xeb96bf6ebadf77d9, 1039, 332),
+];
+const CACHED_POW10_FIRST_E: i16 = -1087;
+const CACHED_POW10_LAST_E: i16 = 1039;
+// Range of exponents the scaled numbers are brought to.
+const GRISU_ALPHA: i16 = -60;
+const GRISU_GAMMA: i16 = -32;
+
+/// Returns the shortest digits of a positive normal `x` and its decimal
+/// exponent, `x` being `0.digits * 10^exp`, as `core` generates them with
+/// Grisu, or `None` where it cannot decide them and `core` falls back to
+/// Dragon4. A port of `format_shortest_opt` from
+/// `core::num::flt2dec::strategy::grisu`.
+fn grisu_shortest(x: f64) -> Option<(Vec<u8>, i16)> {
+    let bits = x.to_bits();
+    let mantissa = bits & ((1 << FRACTION_BITS) - 1) | (1 << FRACTION_BITS);
+    let exponent = (bits >> FRACTION_BITS) as i16 - EXPONENT_BIAS as i16 - FRACTION_BITS as i16;
+    // `mant` and its neighbours `mant - minus` and `mant + plus`, at a shared
+    // exponent. The lower neighbour is twice as close when `x` is a power of two.
+    let (mant, minus, plus, exp) = if mantissa == 1 << FRACTION_BITS {
+        (mantissa << 2, 1, 2, exponent - 2)
+    } else {
+        (mantissa << 1, 1, 1, exponent - 1)
+    };
+
+    let plus = Fp {
+        f: mant + plus,
+        e: exp,
+    }
+    .normalize();
+    let minus = Fp {
+        f: mant - minus,
+        e: exp,
+    }
+    .normalize_to(plus.e);
+    let v = Fp { f: mant, e: exp }.normalize_to(plus.e);
+
+    let range = CACHED_POW10.len() as i32 - 1;
+    let domain = (CACHED_POW10_LAST_E - CACHED_POW10_FIRST_E) as i32;
+    let index = (GRISU_GAMMA - plus.e - 64 - CACHED_POW10_FIRST_E) as i32 * range / domain;
+    let (f, e, minusk) = CACHED_POW10[index as usize];
+    let cached = Fp { f, e };
+    let plus = plus.mul(cached);
+    let minus = minus.mul(cached);
+    let v = v.mul(cached);
+    debug_assert!((GRISU_ALPHA..=GRISU_GAMMA).contains(&plus.e));
+
+    // Digits are generated from `plus1`, the upper end of the unsafe region
+    // around `v`, until they fall within it.
+    let plus1 = plus.f + 1;
+    let minus1 = minus.f - 1;
+    let e = -plus.e as u32;
+    let frac_mask = (1u64 << e) - 1;
+    let plus1int = (plus1 >> e) as u32;
+    let plus1frac = plus1 & frac_mask;
+    let max_kappa = plus1int.ilog10();
+    let exp = max_kappa as i16 - minusk + 1;
+    let delta1 = plus1 - minus1;
+
+    let mut digits = Vec::new();
+    let mut ten_kappa = 10u32.pow(max_kappa);
+    let mut remainder = plus1int;
+    loop {
+        digits.push(b'0' + (remainder / ten_kappa) as u8);
+        remainder %= ten_kappa;
+        let plus1rem = ((remainder as u64) << e) + plus1frac;
+        if plus1rem < delta1 {
+            let ten_kappa = (ten_kappa as u64) << e;
+            return round_and_weed(digits, exp, plus1rem, delta1, plus1 - v.f, ten_kappa, 1);
+        }
+        if digits.len() > max_kappa as usize {
+            break;
+        }
+        ten_kappa /= 10;
+    }
+
+    let mut remainder = plus1frac;
+    let mut threshold = delta1 & frac_mask;
+    let mut ulp = 1;
+    loop {
+        remainder *= 10;
+        threshold *= 10;
+        ulp *= 10;
+        digits.push(b'0' + (remainder >> e) as u8);
+        remainder &= frac_mask;
+        if remainder < threshold {
+            let plus1v = (plus1 - v.f) * ulp;
+            return round_and_weed(digits, exp, remainder, threshold, plus1v, 1 << e, ulp);
+        }
+    }
+}
+
+/// Returns whether decrementing the last digit, which moves the digits from
+/// `plus1 - plus1w` by `ten_kappa` away from `plus1`, brings them closer to
+/// `plus1 - plus1v` while staying above `plus1 - threshold`.
+fn closer_below(plus1w: u64, plus1v: u64, threshold: u64, ten_kappa: u64) -> bool {
+    plus1w < plus1v
+        && threshold - plus1w >= ten_kappa
+        && (plus1w + ten_kappa < plus1v || plus1v - plus1w >= plus1w + ten_kappa - plus1v)
+}
+
+/// Decrements the last digit towards `v`, giving up if the result is not the
+/// closest to both ends of the error interval of `v` or is not safely between
+/// the neighbours of `x`.
+fn round_and_weed(
+    mut digits: Vec<u8>,
+    exp: i16,
+    remainder: u64,
+    threshold: u64,
+    plus1v: u64,
+    ten_kappa: u64,
+    ulp: u64,
+) -> Option<(Vec<u8>, i16)> {
+    let mut plus1w = remainder;
+    while closer_below(plus1w, plus1v - ulp, threshold, ten_kappa) {
+        *digits.last_mut().unwrap() -= 1;
+        plus1w += ten_kappa;
+    }
+    if closer_below(plus1w, plus1v + ulp, threshold, ten_kappa) {
+        return None;
+    }
+    (2 * ulp <= plus1w && plus1w <= threshold - 4 * ulp).then_some((digits, exp))
+}
+
+/// Returns the bytes of the shortest form of `x`, each with whether it is a
+/// digit generated by Grisu rather than a constant, or `None` where the digits
+/// are generated by Dragon4.
+fn shortest_form(x: f64) -> Option<Vec<(u8, bool)>> {
+    let mut form = Vec::new();
+    if x.is_sign_negative() {
+        form.push((b'-', false));
+    }
+    if x == 0.0 {
+        form.push((b'0', false));
+        return Some(form);
+    }
+
+    let (digits, exp) = grisu_shortest(x.abs())?;
+    let generated = |&digit: &u8| (digit, true);
+    if exp <= 0 {
+        form.extend([(b'0', false), (b'.', false)]);
+        form.extend((exp..0).map(|_| (b'0', false)));
+        form.extend(digits.iter().map(generated));
+    } else if (exp as usize) < digits.len() {
+        let (integer, fraction) = digits.split_at(exp as usize);
+        form.extend(integer.iter().map(generated));
+        form.push((b'.', false));
+        form.extend(fraction.iter().map(generated));
+    } else {
+        form.extend(digits.iter().map(generated));
+        form.extend((digits.len()..exp as usize).map(|_| (b'0', false)));
+    }
+    Some(form)
+}
+
+/// Asserts that digits are tainted iff `tainted`, and other characters are
+/// not tainted.
+fn assert_digits(s: &[u8], tainted: bool) {
+    for &byte in s {
+        if tainted && byte.is_ascii_digit() {
+            unsafe { assert_is_tainted_i32(byte as i32) };
+        } else {
+            unsafe { assert_is_not_tainted_i32(byte as i32) };
+        }
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    let mut rng = Random::new();
+    let mut printed = Vec::new();
+    let mut dragon_values = 0;
+    for i in 0..n {
+        let tainted = i.is_multiple_of(TAINTED_PERIOD);
+        let magnitude = 10f64.powi((i % 20) as i32 - 5);
+        let sign = if (i / 2).is_multiple_of(2) { 1.0 } else { -1.0 };
+        let mut x = sign * (1.0 + rng.gen_random(1.0)) * magnitude;
+        if tainted {
+            x = unsafe { taint_f64(x) };
+        }
+
+        printed.clear();
+        write_shortest(x, &mut printed);
+        assert_digits(&printed, tainted);
+        let std_printed = x.to_string();
+        assert_eq!(printed, std_printed.as_bytes());
+
+        let parsed: f64 = std::str::from_utf8(&printed).unwrap().parse().unwrap();
+        assert_eq!(parsed, x);
+        let std_parsed: f64 = std_printed.parse().unwrap();
+        assert_eq!(std_parsed, x);
+        if tainted {
+            unsafe { assert_is_tainted_f64(parsed) };
+            match shortest_form(x) {
+                Some(form) => {
+                    assert!(std_printed.bytes().eq(form.iter().map(|&(byte, _)| byte)));
+                    for (byte, (_, generated)) in std_printed.bytes().zip(form) {
+                        if generated {
+                            unsafe { assert_is_tainted_i32(byte as i32) };
+                        } else {
+                            unsafe { assert_is_not_tainted_i32(byte as i32) };
+                        }
+                    }
+                    unsafe { assert_is_tainted_f64(std_parsed) };
+                }
+                None => {
+                    assert_digits(std_printed.as_bytes(), false);
+                    unsafe { assert_is_not_tainted_f64(std_parsed) };
+                    dragon_values += 1;
+                }
+            }
+        } else {
+            unsafe { assert_is_not_tainted_f64(parsed) };
+            assert_digits(std_printed.as_bytes(), false);
+            unsafe { assert_is_not_tainted_f64(std_parsed) };
+        }
+    }
+
+    dragon_values
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
@@ -0,0 +1,472 @@
+// Formatting benchmark
+// Formats records of partially tainted integers and floats into a `String`
+// with `write!`, going through `core::fmt`: padding and alignment, decimal and
+// hexadecimal integers, a fixed-point type with a hand-written `Display`
+// implementation, and the shortest, fixed precision and exponent forms of f64.
+//
+// The digits of `Fixed` are computed with arithmetic, so they are tainted iff
+// the integer is. `core` loads decimal and hexadecimal digits from tables
+// indexed by the tainted remainder, so they are not tainted, and neither are
+// signs, padding and separators, which are constants selected by control flow.
+//
+// The shortest form of an f64 is generated by Grisu, with arithmetic, except
+// where Grisu cannot decide the digits and Dragon4 selects them by comparisons.
+// A port of the Grisu digit generation tells the two apart, so the digits of
+// the shortest form are asserted tainted iff Grisu generates them, while the
+// zeros and the point around them are constants. The fixed precision and
+// exponent forms round through carries writing constant digits, and their
+// exponent is tainted or not depending on the algorithm, so `benchmark`
+// returns the number of their tainted digits.
+
+use std::fmt::{self, Write};
+use std::ops::Range;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn taint_f64(val: f64) -> f64;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+    fn check_is_tainted_i32(val: i32) -> bool;
+}
+
+const TAINTED_PERIOD: usize = 2;
+const FRACTION_BITS: u32 = 52;
+const EXPONENT_BIAS: i32 = 1023;
+
+/// Fixed-point number with three decimals, stored in thousandths.
+struct Fixed(i32);
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = [0u8; 16];
+        let mut pos = buf.len();
+        let mut n = self.0.unsigned_abs();
+        for _ in 0..3 {
+            pos -= 1;
+            buf[pos] = b'0' + (n % 10) as u8;
+            n /= 10;
+        }
+        pos -= 1;
+        buf[pos] = b'.';
+        loop {
+            pos -= 1;
+            buf[pos] = b'0' + (n % 10) as u8;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        f.pad_integral(self.0 >= 0, "", std::str::from_utf8(&buf[pos..]).unwrap())
+    }
+}
+
+/// Binary floating-point number `f * 2^e` with a 64-bit mantissa.
+#[derive(Clone, Copy)]
+struct Fp {
+    f: u64,
+    e: i16,
+}
+
+impl Fp {
+    /// Multiplies two numbers, rounding the mantissa of the product.
+    fn mul(self, other: Fp) -> Fp {
+        let product = self.f as u128 * other.f as u128;
+        Fp {
+            f: (product >> 64) as u64 + ((product as u64) >> 63),
+            e: self.e + other.e + 64,
+        }
+    }
+
+    fn normalize(self) -> Fp {
+        let zeros = self.f.leading_zeros();
+        Fp {
+            f: self.f << zeros,
+            e: self.e - zeros as i16,
+        }
+    }
+
+    fn normalize_to(self, e: i16) -> Fp {
+        Fp {
+            f: self.f << (self.e - e),
+            e,
+        }
+    }
+}
+
+// Powers of ten `(f, e, k)` with `f * 2^e` approximating `10^k`, every eighth
+// one from `10^-308`, as cached by Grisu in `core`.
+const CACHED_POW10: [(u64, i16, i16); 81] = [
+    (0xe61acf033d1a45df, -1087, -308),
+    (0xab70fe17c79ac6ca, -1060, -300),
+    (0xff77b1fcbebcdc4f, -1034, -292),
+    (0xbe5691ef416bd60c, -1007, -284),
+    (0x8dd01fad907ffc3c, -980, -276),
+    (0xd3515c2831559a83, -954, -268),
+    (0x9d71ac8fada6c9b5, -927, -260),
+    (0xea9c227723ee8bcb, -901, -252),
+    (0xaecc49914078536d, -874, -244),
+    (0x823c12795db6ce57, -847, -236),
+    (0xc21094364dfb5637, -821, -228),
+    (0x9096ea6f3848984f, -794, -220),
+    (0xd77485cb25823ac7, -768, -212),
+    (0xa086cfcd97bf97f4, -741, -204),
+    (0xef340a98172aace5, -715, -196),
+    (0xb23867fb2a35b28e, -688, -188),
+    (0x84c8d4dfd2c63f3b, -661, -180),
+    (0xc5dd44271ad3cdba, -635, -172),
+    (0x936b9fcebb25c996, -608, -164),
+    (0xdbac6c247d62a584, -582, -156),
+    (0xa3ab66580d5fdaf6, -555, -148),
+    (0xf3e2f893dec3f126, -529, -140),
+    (0xb5b5ada8aaff80b8, -502, -132),
+    (0x87625f056c7c4a8b, -475, -124),
+    (0xc9bcff6034c13053, -449, -116),
+    (0x964e858c91ba2655, -422, -108),
+    (0xdff9772470297ebd, -396, -100),
+    (0xa6dfbd9fb8e5b88f, -369, -92),
+    (0xf8a95fcf88747d94, -343, -84),
+    (0xb94470938fa89bcf, -316, -76),
+    (0x8a08f0f8bf0f156b, -289, -68),
+    (0xcdb02555653131b6, -263, -60),
+    (0x993fe2c6d07b7fac, -236, -52),
+    (0xe45c10c42a2b3b06, -210, -44),
+    (0xaa242499697392d3, -183, -36),
+    (0xfd87b5f28300ca0e, -157, -28),
+    (0xbce5086492111aeb, -130, -20),
+    (0x8cbccc096f5088cc, -103, -12),
+    (0xd1b71758e219652c, -77, -4),
+    (0x9c40000000000000, -50, 4),
+    (0xe8d4a51000000000, -24, 12),
+    (0xad78ebc5ac620000, 3, 20),
+    (0x813f3978f8940984, 30, 28),
+    (0xc097ce7bc90715b3, 56, 36),
+    (0x8f7e32ce7bea5c70, 83, 44),
+    (0xd5d238a4abe98068, 109, 52),
+    (0x9f4f2726179a2245, 136, 60),
+    (0xed63a231d4c4fb27, 162, 68),
+    (0xb0de65388cc8ada8, 189, 76),
+    (0x83c7088e1aab65db, 216, 84),
+    (0xc45d1df942711d9a, 242, 92),
+    (0x924d692ca61be758, 269, 100),
+    (0xda01ee641a708dea, 295, 108),
+    (0xa26da3999aef774a, 322, 116),
+    (0xf209787bb47d6b85, 348, 124),
+    (0xb454e4a179dd1877, 375, 132),
+    (0x865b86925b9bc5c2, 402, 140),
+    (0xc83553c5c8965d3d, 428, 148),
+    (0x952ab45cfa97a0b3, 455, 156),
+    (0xde469fbd99a05fe3, 481, 164),
+    (0xa59bc234db398c25, 508, 172),
+    (0xf6c69a72a3989f5c, 534, 180),
+    (0xb7dcbf5354e9bece, 561, 188),
+    (0x88fcf317f22241e2, 588, 196),
+    (0xcc20ce9bd35c78a5, 614, 204),
+    (0x98165af37b2153df, 641, 212),
+    (0xe2a0b5dc971f303a, 667, 220),
+    (0xa8d9d1535ce3b396, 694, 228),
+    (0xfb9b7cd9a4a7443c, 720, 236),
+    (0xbb764c4ca7a44410, 747, 244),
+    (0x8bab8eefb6409c1a, 774, 252),
+    (0xd01fef10a657842c, 800, 260),
+    (0x9b10a4e5e9913129, 827, 268),
+    (0xe7109bfba19c0c9d, 853, 276),
+    (0xac2820d9623bf429, 880, 284),
+    (0x80444b5e7aa7cf85, 907, 292),
+    (0xbf21e44003acdd2d, 933, 300),
+    (0x8e679c2f5e44ff8f, 960, 308),
+    (0xd433179d9c8cb841, 986, 316),
+    (0x9e19db92b4e31ba9, 1013, 324),
+    (0xeb96bf6ebadf77d9, 1039, 332),
+];
+const CACHED_POW10_FIRST_E: i16 = -1087;
+const CACHED_POW10_LAST_E: i16 = 1039;
+// Range of exponents the scaled numbers are brought to.
+const GRISU_ALPHA: i16 = -60;
+const GRISU_GAMMA: i16 = -32;
+
+/// Returns the shortest digits of a positive normal `x` and its decimal
+/// exponent, `x` being `0.digits * 10^exp`, as `core` generates them with
+/// Grisu, or `None` where it cannot decide them and `core` falls back to
+/// Dragon4. A port of `format_shortest_opt` from
+/// `core::num::flt2dec::strategy::grisu`.
+fn grisu_shortest(x: f64) -> Option<(Vec<u8>, i16)> {
+    let bits = x.to_bits();
+    let mantissa = bits & ((1 << FRACTION_BITS) - 1) | (1 << FRACTION_BITS);
+    let exponent = (bits >> FRACTION_BITS) as i16 - EXPONENT_BIAS as i16 - FRACTION_BITS as i16;
+    // `mant` and its neighbours `mant - minus` and `mant + plus`, at a shared
+    // exponent. The lower neighbour is twice as close when `x` is a power of two.
+    let (mant, minus, plus, exp) = if mantissa == 1 << FRACTION_BITS {
+        (mantissa << 2, 1, 2, exponent - 2)
+    } else {
+        (mantissa << 1, 1, 1, exponent - 1)
+    };
+
+    let plus = Fp {
+        f: mant + plus,
+        e: exp,
+    }
+    .normalize();
+    let minus = Fp {
+        f: mant - minus,
+        e: exp,
+    }
+    .normalize_to(plus.e);
+    let v = Fp { f: mant, e: exp }.normalize_to(plus.e);
+
+    let range = CACHED_POW10.len() as i32 - 1;
+    let domain = (CACHED_POW10_LAST_E - CACHED_POW10_FIRST_E) as i32;
+    let index = (GRISU_GAMMA - plus.e - 64 - CACHED_POW10_FIRST_E) as i32 * range / domain;
+    let (f, e, minusk) = CACHED_POW10[index as usize];
+    let cached = Fp { f, e };
+    let plus = plus.mul(cached);
+    let minus = minus.mul(cached);
+    let v = v.mul(cached);
+    debug_assert!((GRISU_ALPHA..=GRISU_GAMMA).contains(&plus.e));
+
+    // Digits are generated from `plus1`, the upper end of the unsafe region
+    // around `v`, until they fall within it.
+    let plus1 = plus.f + 1;
+    let minus1 = minus.f - 1;
+    let e = -plus.e as u32;
+    let frac_mask = (1u64 << e) - 1;
+    let plus1int = (plus1 >> e) as u32;
+    let plus1frac = plus1 & frac_mask;
+    let max_kappa = plus1int.ilog10();
+    let exp = max_kappa as i16 - minusk + 1;
+    let delta1 = plus1 - minus1;
+
+    let mut digits = Vec::new();
+    let mut ten_kappa = 10u32.pow(max_kappa);
+    let mut remainder = plus1int;
+    loop {
+        digits.push(b'0' + (remainder / ten_kappa) as u8);
+        remainder %= ten_kappa;
+        let plus1rem = ((remainder as u64) << e) + plus1frac;
+        if plus1rem < delta1 {
+            let ten_kappa = (ten_kappa as u64) << e;
+            return round_and_weed(digits, exp, plus1rem, delta1, plus1 - v.f, ten_kappa, 1);
+        }
+        if digits.len() > max_kappa as usize {
+            break;
+        }
+        ten_kappa /= 10;
+    }
+
+    let mut remainder = plus1frac;
+    let mut threshold = delta1 & frac_mask;
+    let mut ulp = 1;
+    loop {
+        remainder *= 10;
+        threshold *= 10;
+        ulp *= 10;
+        digits.push(b'0' + (remainder >> e) as u8);
+        remainder &= frac_mask;
+        if remainder < threshold {
+            let plus1v = (plus1 - v.f) * ulp;
+            return round_and_weed(digits, exp, remainder, threshold, plus1v, 1 << e, ulp);
+        }
+    }
+}
+
+/// Returns whether decrementing the last digit, which moves the digits from
+/// `plus1 - plus1w` by `ten_kappa` away from `plus1`, brings them closer to
+/// `plus1 - plus1v` while staying above `plus1 - threshold`.
+fn closer_below(plus1w: u64, plus1v: u64, threshold: u64, ten_kappa: u64) -> bool {
+    plus1w < plus1v
+        && threshold - plus1w >= ten_kappa
+        && (plus1w + ten_kappa < plus1v || plus1v - plus1w >= plus1w + ten_kappa - plus1v)
+}
+
+/// Decrements the last digit towards `v`, giving up if the result is not the
+/// closest to both ends of the error interval of `v` or is not safely between
+/// the neighbours of `x`.
+fn round_and_weed(
+    mut digits: Vec<u8>,
+    exp: i16,
+    remainder: u64,
+    threshold: u64,
+    plus1v: u64,
+    ten_kappa: u64,
+    ulp: u64,
+) -> Option<(Vec<u8>, i16)> {
+    let mut plus1w = remainder;
+    while closer_below(plus1w, plus1v - ulp, threshold, ten_kappa) {
+        *digits.last_mut().unwrap() -= 1;
+        plus1w += ten_kappa;
+    }
+    if closer_below(plus1w, plus1v + ulp, threshold, ten_kappa) {
+        return None;
+    }
+    (2 * ulp <= plus1w && plus1w <= threshold - 4 * ulp).then_some((digits, exp))
+}
+
+/// Returns the bytes of the shortest form of `x`, each with whether it is a
+/// digit generated by Grisu rather than a constant, or `None` where the digits
+/// are generated by Dragon4.
+fn shortest_form(x: f64) -> Option<Vec<(u8, bool)>> {
+    let mut form = Vec::new();
+    if x.is_sign_negative() {
+        form.push((b'-', false));
+    }
+    if x == 0.0 {
+        form.push((b'0', false));
+        return Some(form);
+    }
+
+    let (digits, exp) = grisu_shortest(x.abs())?;
+    let generated = |&digit: &u8| (digit, true);
+    if exp <= 0 {
+        form.extend([(b'0', false), (b'.', false)]);
+        form.extend((exp..0).map(|_| (b'0', false)));
+        form.extend(digits.iter().map(generated));
+    } else if (exp as usize) < digits.len() {
+        let (integer, fraction) = digits.split_at(exp as usize);
+        form.extend(integer.iter().map(generated));
+        form.push((b'.', false));
+        form.extend(fraction.iter().map(generated));
+    } else {
+        form.extend(digits.iter().map(generated));
+        form.extend((digits.len()..exp as usize).map(|_| (b'0', false)));
+    }
+    Some(form)
+}
+
+/// Appends a formatted field to `line`, returning the range it occupies.
+fn write_field(line: &mut String, args: fmt::Arguments) -> Range<usize> {
+    let start = line.len();
+    line.write_fmt(args).unwrap();
+    let range = start..line.len();
+    line.push(' ');
+    range
+}
+
+struct Checker<'a> {
+    line: &'a [u8],
+    // Fixed precision and exponent digits reported as tainted.
+    tainted: usize,
+}
+
+impl Checker<'_> {
+    fn assert_not_tainted(&self, range: Range<usize>) {
+        for &byte in &self.line[range] {
+            unsafe { assert_is_not_tainted_i32(byte as i32) };
+        }
+    }
+
+    /// Asserts that digits are tainted and other characters are not.
+    fn assert_digits_tainted(&self, range: Range<usize>) {
+        for &byte in &self.line[range] {
+            if byte.is_ascii_digit() {
+                unsafe { assert_is_tainted_i32(byte as i32) };
+            } else {
+                unsafe { assert_is_not_tainted_i32(byte as i32) };
+            }
+        }
+    }
+
+    /// Asserts that the digits Grisu generates for the shortest form of `x`
+    /// are tainted and that the rest of it, or all of it where Dragon4
+    /// generates the digits, is not.
+    fn assert_shortest_tainted(&self, range: Range<usize>, x: f64) {
+        let line = &self.line[range];
+        match shortest_form(x) {
+            Some(form) => {
+                assert!(line.iter().eq(form.iter().map(|(byte, _)| byte)));
+                for (&byte, &(_, generated)) in line.iter().zip(&form) {
+                    if generated {
+                        unsafe { assert_is_tainted_i32(byte as i32) };
+                    } else {
+                        unsafe { assert_is_not_tainted_i32(byte as i32) };
+                    }
+                }
+            }
+            None => {
+                for &byte in line {
+                    unsafe { assert_is_not_tainted_i32(byte as i32) };
+                }
+            }
+        }
+    }
+
+    /// Counts tainted digits, asserting that other characters are not tainted.
+    fn check_digits(&mut self, range: Range<usize>) {
+        for &byte in &self.line[range] {
+            if byte.is_ascii_digit() {
+                if unsafe { check_is_tainted_i32(byte as i32) } {
+                    self.tainted += 1;
+                }
+            } else {
+                unsafe { assert_is_not_tainted_i32(byte as i32) };
+            }
+        }
+    }
+}
+
+fn value(i: usize) -> i32 {
+    ((i as u32).wrapping_mul(2654435761) >> 7) as i32 - (1 << 24)
+}
+
+fn benchmark(n: usize) -> i32 {
+    assert_eq!(Fixed(-1234).to_string(), "-1.234");
+    assert_eq!(format!("{:>8}", Fixed(5)), "   0.005");
+
+    let mut line = String::new();
+    let mut tainted_digits = 0;
+    for i in 0..n {
+        let tainted = i.is_multiple_of(TAINTED_PERIOD);
+        let mut v = value(i);
+        let mut x = v as f64 / 7.0;
+        if tainted {
+            v = unsafe { taint_i32(v) };
+            x = unsafe { taint_f64(x) };
+        }
+
+        line.clear();
+        let id = write_field(&mut line, format_args!("{:>8}", i));
+        let decimal = write_field(&mut line, format_args!("{:+}", v));
+        let hex = write_field(&mut line, format_args!("{:#010x}", v as u32));
+        let fixed = write_field(&mut line, format_args!("{:>16}", Fixed(v)));
+        let shortest = write_field(&mut line, format_args!("{}", x));
+        let precision = write_field(&mut line, format_args!("{:<16.3}", x));
+        let exponent = write_field(&mut line, format_args!("{:e}", x));
+
+        assert_eq!(line[decimal.clone()].parse::<i32>(), Ok(v));
+        assert_eq!(
+            u32::from_str_radix(&line[hex.start + 2..hex.end], 16),
+            Ok(v as u32)
+        );
+        assert_eq!(
+            line[fixed.clone()].trim_start(),
+            format!("{:.3}", v as f64 / 1000.0)
+        );
+        assert_eq!(line[shortest.clone()].parse::<f64>(), Ok(x));
+        assert_eq!(line[exponent.clone()].parse::<f64>(), Ok(x));
+
+        let mut checker = Checker {
+            line: line.as_bytes(),
+            tainted: 0,
+        };
+        checker.assert_not_tainted(id);
+        if tainted {
+            checker.assert_not_tainted(decimal);
+            checker.assert_not_tainted(hex);
+            checker.assert_digits_tainted(fixed);
+            checker.assert_shortest_tainted(shortest, x);
+            checker.check_digits(precision);
+            checker.check_digits(exponent);
+        } else {
+            for range in [decimal, hex, fixed, shortest, precision, exponent] {
+                checker.assert_not_tainted(range);
+            }
+        }
+        tainted_digits += checker.tainted;
+    }
+
+    tainted_digits as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
@@ -0,0 +1,119 @@
+// Game of Life benchmark
+// Runs Conway's Game of Life on a bounded grid of `u8` cells seeded with random
+// soup and one glider whose cells are tainted. The next state of a cell is
+// computed from the sum of its neighbors without branching, so after k
+// generations exactly the cells within Chebyshev distance k of the glider's
+// initial cells, its light cone, are tainted.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const SIZE: usize = 256;
+// The grid is surrounded by a border of dead cells that are never updated.
+const STRIDE: usize = SIZE + 2;
+const SOUP_DENSITY: f64 = 0.3;
+const GLIDER: [(usize, usize); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+const GLIDER_OFFSET: usize = 8;
+// Soup is kept this far away from the glider so that it starts out alone.
+const GLIDER_CLEARANCE: usize = 8;
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+fn index(x: usize, y: usize) -> usize {
+    (y + 1) * STRIDE + x + 1
+}
+
+fn setup_grid() -> Vec<u8> {
+    let mut rng = Random::new();
+    let mut grid = vec![0u8; STRIDE * STRIDE];
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let near_glider =
+                x < GLIDER_OFFSET + GLIDER_CLEARANCE && y < GLIDER_OFFSET + GLIDER_CLEARANCE;
+            if !near_glider && rng.gen_random(1.0) < SOUP_DENSITY {
+                grid[index(x, y)] = 1;
+            }
+        }
+    }
+    for &(x, y) in &GLIDER {
+        grid[index(GLIDER_OFFSET + x, GLIDER_OFFSET + y)] = unsafe { taint_i32(1) as u8 };
+    }
+    grid
+}
+
+/// Computes the next generation into `next`. A cell lives iff the sum of its
+/// neighbors, or'ed with its own state, is 3.
+fn step(grid: &[u8], next: &mut [u8]) {
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let i = index(x, y);
+            let neighbors = grid[i - STRIDE - 1]
+                + grid[i - STRIDE]
+                + grid[i - STRIDE + 1]
+                + grid[i - 1]
+                + grid[i + 1]
+                + grid[i + STRIDE - 1]
+                + grid[i + STRIDE]
+                + grid[i + STRIDE + 1];
+            next[i] = ((neighbors | grid[i]) == 3) as u8;
+        }
+    }
+}
+
+/// Returns whether the cell is within the light cone of the glider after
+/// `generations` generations.
+fn in_light_cone(x: usize, y: usize, generations: usize) -> bool {
+    GLIDER.iter().any(|&(gx, gy)| {
+        let (gx, gy) = (GLIDER_OFFSET + gx, GLIDER_OFFSET + gy);
+        x.abs_diff(gx).max(y.abs_diff(gy)) <= generations
+    })
+}
+
+fn benchmark(generations: usize) -> i32 {
+    let mut grid = setup_grid();
+    let mut next = vec![0u8; STRIDE * STRIDE];
+    for _ in 0..generations {
+        step(&grid, &mut next);
+        std::mem::swap(&mut grid, &mut next);
+    }
+
+    let mut live = 0;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let cell = grid[index(x, y)];
+            if in_light_cone(x, y, generations) {
+                unsafe { assert_is_tainted_i32(cell as i32) };
+            } else {
+                unsafe { assert_is_not_tainted_i32(cell as i32) };
+            }
+            live += cell as i32;
+        }
+    }
+    live
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
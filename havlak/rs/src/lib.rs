@@ -0,0 +1,373 @@
+// Havlak benchmark
+// Adapted from Robert Hundt's "Loop Recognition in C++/Java/Go/Scala"
+// benchmark, which finds the loops of a large synthetic control flow graph with
+// Havlak's algorithm. Basic blocks are `Vec`-indexed, and the union-find used
+// to collapse loop bodies is keyed by basic block id. The ids of the blocks in
+// every other loop tree are tainted, so tainted ids end up scattered over the
+// union-find parent array and the loop structure graph.
+//
+// Ids are only copied, even where the union-find follows parents and
+// compresses paths by loading ids at indices given by other ids, so every
+// stored id keeps the taint of the block it names.
+
+use std::collections::{HashSet, VecDeque};
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const UNVISITED: usize = usize::MAX;
+
+// Iterations per loop tree, and base loops per iteration. Every base loop
+// contains three loops, every iteration adds one more around its base loops.
+const TREE_ITERATIONS: usize = 100;
+const BASE_LOOPS: usize = 25;
+const LOOPS_PER_BASE_LOOP: usize = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+enum BlockType {
+    NonHeader,
+    Reducible,
+    SelfLoop,
+    Irreducible,
+    Dead,
+}
+
+struct BasicBlock {
+    in_edges: Vec<usize>,
+    out_edges: Vec<usize>,
+}
+
+struct Cfg {
+    blocks: Vec<BasicBlock>,
+    // Whether each block's id is tainted wherever it is stored, for the checks.
+    tainted: Vec<bool>,
+    taint_new_blocks: bool,
+}
+
+impl Cfg {
+    fn new() -> Self {
+        Cfg {
+            blocks: Vec::new(),
+            tainted: Vec::new(),
+            taint_new_blocks: false,
+        }
+    }
+
+    fn create_node(&mut self, id: usize) -> usize {
+        while self.blocks.len() <= id {
+            self.blocks.push(BasicBlock {
+                in_edges: Vec::new(),
+                out_edges: Vec::new(),
+            });
+            self.tainted.push(self.taint_new_blocks);
+        }
+        id
+    }
+
+    /// Returns the id to store when referring to block `id`.
+    fn block_id(&self, id: usize) -> usize {
+        if self.tainted[id] {
+            unsafe { taint_i32(id as i32) as usize }
+        } else {
+            id
+        }
+    }
+
+    fn create_edge(&mut self, from: usize, to: usize) {
+        self.create_node(from);
+        self.create_node(to);
+        let from_id = self.block_id(from);
+        let to_id = self.block_id(to);
+        self.blocks[from].out_edges.push(to_id);
+        self.blocks[to].in_edges.push(from_id);
+    }
+
+    fn build_diamond(&mut self, start: usize) -> usize {
+        self.create_edge(start, start + 1);
+        self.create_edge(start, start + 2);
+        self.create_edge(start + 1, start + 3);
+        self.create_edge(start + 2, start + 3);
+        start + 3
+    }
+
+    fn build_straight(&mut self, start: usize, n: usize) -> usize {
+        for i in 0..n {
+            self.create_edge(start + i, start + i + 1);
+        }
+        start + n
+    }
+
+    fn build_base_loop(&mut self, from: usize) -> usize {
+        let header = self.build_straight(from, 1);
+        let diamond1 = self.build_diamond(header);
+        let d11 = self.build_straight(diamond1, 1);
+        let diamond2 = self.build_diamond(d11);
+        let footer = self.build_straight(diamond2, 1);
+        self.create_edge(diamond2, d11);
+        self.create_edge(diamond1, header);
+        self.create_edge(footer, from);
+        self.build_straight(footer, 1)
+    }
+}
+
+struct SimpleLoop {
+    header: usize,
+    parent: Option<usize>,
+    nodes: Vec<usize>,
+    is_reducible: bool,
+}
+
+/// Union-find over basic block ids, with path compression.
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn find(&mut self, id: usize) -> usize {
+        let mut path = Vec::new();
+        let mut node = id;
+        while node != self.parent[node] {
+            if self.parent[node] != self.parent[self.parent[node]] {
+                path.push(node);
+            }
+            node = self.parent[node];
+        }
+        for &on_path in &path {
+            self.parent[on_path] = self.parent[node];
+        }
+        node
+    }
+
+    fn union(&mut self, id: usize, header: usize) {
+        self.parent[id] = header;
+    }
+}
+
+struct LoopFinder<'a> {
+    cfg: &'a Cfg,
+    union_find: UnionFind,
+    loops: Vec<SimpleLoop>,
+}
+
+fn is_ancestor(w: usize, v: usize, last: &[usize]) -> bool {
+    w <= v && v <= last[w]
+}
+
+impl<'a> LoopFinder<'a> {
+    fn new(cfg: &'a Cfg) -> Self {
+        LoopFinder {
+            cfg,
+            union_find: UnionFind {
+                parent: vec![UNVISITED; cfg.blocks.len()],
+            },
+            loops: Vec::new(),
+        }
+    }
+
+    /// Numbers the blocks reachable from block 0 in depth-first preorder,
+    /// returning the block of every number. `last[w]` is set to the highest
+    /// number in the subtree of `w`.
+    fn depth_first_search(&mut self, number: &mut [usize], last: &mut [usize]) -> Vec<usize> {
+        let mut blocks = Vec::with_capacity(number.len());
+        let mut stack: Vec<(usize, usize)> = vec![(0, 0)];
+        number[0] = 0;
+        blocks.push(0);
+        self.union_find.parent[0] = 0;
+
+        while let Some(top) = stack.last_mut() {
+            let (block, edge) = *top;
+            if edge < self.cfg.blocks[block].out_edges.len() {
+                top.1 += 1;
+                let target = self.cfg.blocks[block].out_edges[edge];
+                if number[target] == UNVISITED {
+                    number[target] = blocks.len();
+                    blocks.push(target);
+                    self.union_find.parent[target] = target;
+                    stack.push((target, 0));
+                }
+            } else {
+                last[number[block]] = blocks.len() - 1;
+                stack.pop();
+            }
+        }
+
+        blocks
+    }
+
+    fn find_loops(&mut self) {
+        let size = self.cfg.blocks.len();
+        let mut number = vec![UNVISITED; size];
+        let mut last = vec![0; size];
+        let mut non_back_preds: Vec<HashSet<usize>> = vec![HashSet::new(); size];
+        let mut back_preds: Vec<Vec<usize>> = vec![Vec::new(); size];
+        let mut types = vec![BlockType::Dead; size];
+        let mut block_loop: Vec<Option<usize>> = vec![None; size];
+
+        let blocks = self.depth_first_search(&mut number, &mut last);
+
+        // Classify the predecessors of every block as back edges or not.
+        for (w, &block) in blocks.iter().enumerate() {
+            types[w] = BlockType::NonHeader;
+            for &pred in &self.cfg.blocks[block].in_edges {
+                let v = number[pred];
+                if v == UNVISITED {
+                    continue;
+                }
+                if is_ancestor(w, v, &last) {
+                    back_preds[w].push(v);
+                } else {
+                    non_back_preds[w].insert(v);
+                }
+            }
+        }
+
+        // Collapse loop bodies bottom-up, innermost loops first.
+        for w in (0..blocks.len()).rev() {
+            let mut node_pool: Vec<usize> = Vec::new();
+            for &v in &back_preds[w] {
+                if v != w {
+                    node_pool.push(self.union_find.find(blocks[v]));
+                } else {
+                    types[w] = BlockType::SelfLoop;
+                }
+            }
+
+            let mut work_list: VecDeque<usize> = node_pool.iter().copied().collect();
+            if !node_pool.is_empty() {
+                types[w] = BlockType::Reducible;
+            }
+
+            while let Some(x) = work_list.pop_front() {
+                let preds: Vec<usize> = non_back_preds[number[x]].iter().copied().collect();
+                for y in preds {
+                    let ydash = self.union_find.find(blocks[y]);
+                    let ydash_number = number[ydash];
+                    if !is_ancestor(w, ydash_number, &last) {
+                        types[w] = BlockType::Irreducible;
+                        non_back_preds[w].insert(ydash_number);
+                    } else if ydash_number != w && !node_pool.contains(&ydash) {
+                        work_list.push_back(ydash);
+                        node_pool.push(ydash);
+                    }
+                }
+            }
+
+            if !node_pool.is_empty() || types[w] == BlockType::SelfLoop {
+                let loop_index = self.loops.len();
+                self.loops.push(SimpleLoop {
+                    header: blocks[w],
+                    parent: None,
+                    nodes: vec![blocks[w]],
+                    is_reducible: types[w] != BlockType::Irreducible,
+                });
+                block_loop[number[blocks[w]]] = Some(loop_index);
+
+                for &node in &node_pool {
+                    self.union_find.union(node, blocks[w]);
+                    match block_loop[number[node]] {
+                        Some(inner) => self.loops[inner].parent = Some(loop_index),
+                        None => self.loops[loop_index].nodes.push(node),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn assert_id_taint(cfg: &Cfg, id: usize) {
+    if cfg.tainted[id] {
+        unsafe { assert_is_tainted_i32(id as i32) };
+    } else {
+        unsafe { assert_is_not_tainted_i32(id as i32) };
+    }
+}
+
+/// Builds the benchmark's control flow graph with `trees` parallel loop trees,
+/// tainting the blocks of every odd tree.
+fn setup_cfg(trees: usize) -> Cfg {
+    let mut cfg = Cfg::new();
+    cfg.create_node(0);
+    cfg.build_base_loop(0);
+    cfg.create_node(1);
+    cfg.create_edge(0, 2);
+
+    let mut n = 2;
+    for tree in 0..trees {
+        cfg.taint_new_blocks = tree % 2 == 1;
+        cfg.create_node(n + 1);
+        cfg.create_edge(2, n + 1);
+        n += 1;
+
+        for _ in 0..TREE_ITERATIONS {
+            let top = n;
+            n = cfg.build_straight(n, 1);
+            for _ in 0..BASE_LOOPS {
+                n = cfg.build_base_loop(n);
+            }
+            let bottom = cfg.build_straight(n, 1);
+            cfg.create_edge(n, top);
+            n = bottom;
+        }
+
+        cfg.create_edge(n, 1);
+    }
+
+    cfg
+}
+
+/// As in the original benchmark, the base loop of the entry overlaps the
+/// blocks of the first loop tree, which leaves a single loop of its own, or
+/// all of its loops without loop trees.
+fn expected_loop_count(trees: usize) -> usize {
+    if trees == 0 {
+        return LOOPS_PER_BASE_LOOP;
+    }
+    1 + trees * TREE_ITERATIONS * (BASE_LOOPS * LOOPS_PER_BASE_LOOP + 1)
+}
+
+fn check_loops(finder: &LoopFinder, trees: usize) {
+    let cfg = finder.cfg;
+    for simple_loop in &finder.loops {
+        assert_id_taint(cfg, simple_loop.header);
+        for &node in &simple_loop.nodes {
+            assert_id_taint(cfg, node);
+        }
+    }
+
+    // The entry branches into the body of its base loop, which makes one loop
+    // irreducible. The loop trees exit into that base loop, which nests
+    // everything in a single outermost loop and makes another one irreducible.
+    let outermost = finder.loops.iter().filter(|l| l.parent.is_none()).count();
+    let irreducible = finder.loops.iter().filter(|l| !l.is_reducible).count();
+    assert_eq!(outermost, 1);
+    assert_eq!(irreducible, if trees == 0 { 1 } else { 2 });
+
+    for id in 0..cfg.blocks.len() {
+        let parent = finder.union_find.parent[id];
+        if parent != UNVISITED {
+            assert_id_taint(cfg, parent);
+        }
+    }
+}
+
+fn benchmark(trees: usize) -> i32 {
+    let cfg = setup_cfg(trees);
+
+    let mut finder = LoopFinder::new(&cfg);
+    finder.find_loops();
+    assert_eq!(finder.loops.len(), expected_loop_count(trees));
+    check_loops(&finder, trees);
+
+    let loop_count = finder.loops.len() as i32;
+    unsafe { assert_is_not_tainted_i32(loop_count) };
+    loop_count
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
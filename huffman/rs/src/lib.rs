@@ -0,0 +1,313 @@
+// Huffman benchmark
+// Builds a Huffman code from the symbol frequencies of a FASTA-like sequence,
+// generated as in fasta, packs the codes into a stream of `u32` words and
+// decodes it again. Every other line of the sequence is tainted, so most words
+// mix bits of tainted and untainted codes.
+//
+// A word is tainted iff it holds bits of a tainted code, and shadows are per
+// value, so every bit read from such a word is tainted. The decoder walks the
+// tree by indexing the children of a node with these bits, and a decoded symbol
+// is the constant of the leaf it reaches, so it is never tainted.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const LINE_LENGTH: usize = 60;
+const SYMBOLS: usize = 256;
+const WORD_BITS: usize = 32;
+
+struct AminoAcid {
+    c: u8,
+    p: f64,
+}
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+fn iub() -> Vec<AminoAcid> {
+    let mut iub = vec![
+        AminoAcid { c: b'a', p: 0.27 },
+        AminoAcid { c: b'c', p: 0.12 },
+        AminoAcid { c: b'g', p: 0.12 },
+        AminoAcid { c: b't', p: 0.27 },
+        AminoAcid { c: b'B', p: 0.02 },
+        AminoAcid { c: b'D', p: 0.02 },
+        AminoAcid { c: b'H', p: 0.02 },
+        AminoAcid { c: b'K', p: 0.02 },
+        AminoAcid { c: b'M', p: 0.02 },
+        AminoAcid { c: b'N', p: 0.02 },
+        AminoAcid { c: b'R', p: 0.02 },
+        AminoAcid { c: b'S', p: 0.02 },
+        AminoAcid { c: b'V', p: 0.02 },
+        AminoAcid { c: b'W', p: 0.02 },
+        AminoAcid { c: b'Y', p: 0.02 },
+    ];
+
+    let mut cp = 0.0;
+    for acid in iub.iter_mut() {
+        cp += acid.p;
+        acid.p = cp;
+    }
+    iub
+}
+
+fn select_random(rng: &mut Random, acids: &[AminoAcid]) -> u8 {
+    let r = rng.gen_random(1.0);
+    acids
+        .iter()
+        .find(|acid| r < acid.p)
+        .unwrap_or(&acids[acids.len() - 1])
+        .c
+}
+
+struct Input {
+    symbols: Vec<u8>,
+    // Whether each symbol is tainted, for the checks.
+    tainted: Vec<bool>,
+}
+
+/// Generates `n` lines of sequence, tainting the symbols of every odd line but
+/// not the newlines.
+fn setup_input(n: usize) -> Input {
+    let acids = iub();
+    let mut rng = Random::new();
+    let mut input = Input {
+        symbols: Vec::with_capacity(n * (LINE_LENGTH + 1)),
+        tainted: Vec::with_capacity(n * (LINE_LENGTH + 1)),
+    };
+
+    for line in 0..n {
+        let tainted = !line.is_multiple_of(2);
+        for _ in 0..LINE_LENGTH {
+            let symbol = select_random(&mut rng, &acids);
+            input.symbols.push(if tainted {
+                unsafe { taint_i32(symbol as i32) as u8 }
+            } else {
+                symbol
+            });
+            input.tainted.push(tainted);
+        }
+        input.symbols.push(b'\n');
+        input.tainted.push(false);
+    }
+    input
+}
+
+enum Node {
+    Leaf(u8),
+    // Children for a 0 and a 1 bit.
+    Internal([usize; 2]),
+}
+
+#[derive(Clone, Copy, Default)]
+struct Code {
+    // Path from the root, first step in the least significant bit.
+    bits: u32,
+    len: usize,
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+impl Tree {
+    /// Builds the tree by repeatedly merging the two least frequent nodes. Ties
+    /// are broken by node index, which keeps the code deterministic.
+    fn new(symbols: &[u8]) -> Self {
+        let mut counts = [0u32; SYMBOLS];
+        for &symbol in symbols {
+            counts[symbol as usize] += 1;
+        }
+
+        let mut nodes = Vec::new();
+        let mut heap = BinaryHeap::new();
+        for (symbol, &count) in counts.iter().enumerate() {
+            if count > 0 {
+                heap.push(Reverse((count, nodes.len())));
+                nodes.push(Node::Leaf(symbol as u8));
+            }
+        }
+
+        while heap.len() > 1 {
+            let Reverse((left_count, left)) = heap.pop().unwrap();
+            let Reverse((right_count, right)) = heap.pop().unwrap();
+            heap.push(Reverse((left_count + right_count, nodes.len())));
+            nodes.push(Node::Internal([left, right]));
+        }
+
+        let Reverse((_, root)) = heap.pop().unwrap();
+        Tree { nodes, root }
+    }
+
+    fn codes(&self) -> Vec<Code> {
+        let mut codes = vec![Code::default(); SYMBOLS];
+        let mut stack = vec![(self.root, Code::default())];
+        while let Some((node, code)) = stack.pop() {
+            match self.nodes[node] {
+                Node::Leaf(symbol) => codes[symbol as usize] = code,
+                Node::Internal([left, right]) => {
+                    let len = code.len + 1;
+                    stack.push((
+                        left,
+                        Code {
+                            bits: code.bits,
+                            len,
+                        },
+                    ));
+                    stack.push((
+                        right,
+                        Code {
+                            bits: code.bits | 1 << code.len,
+                            len,
+                        },
+                    ));
+                }
+            }
+        }
+        codes
+    }
+}
+
+struct BitWriter {
+    words: Vec<u32>,
+    len: usize,
+}
+
+impl BitWriter {
+    /// Ors `code` into the stream, spilling into a new word when it does not
+    /// fit the current one.
+    fn write(&mut self, code: Code) {
+        let offset = self.len % WORD_BITS;
+        if offset == 0 {
+            self.words.push(0);
+        }
+        let last = self.words.len() - 1;
+        self.words[last] |= code.bits << offset;
+        if offset + code.len > WORD_BITS {
+            self.words.push(code.bits >> (WORD_BITS - offset));
+        }
+        self.len += code.len;
+    }
+}
+
+struct BitReader<'a> {
+    words: &'a [u32],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self) -> u32 {
+        let bit = (self.words[self.pos / WORD_BITS] >> (self.pos % WORD_BITS)) & 1;
+        self.pos += 1;
+        bit
+    }
+}
+
+fn encode(input: &Input, codes: &[Code]) -> BitWriter {
+    let mut writer = BitWriter {
+        words: Vec::new(),
+        len: 0,
+    };
+    for (&symbol, &tainted) in input.symbols.iter().zip(&input.tainted) {
+        let mut code = codes[symbol as usize];
+        // As in reverse-complement, the table lookup does not propagate the
+        // taint of the symbol, so its code is tainted explicitly.
+        if tainted {
+            code.bits = unsafe { taint_i32(code.bits as i32) as u32 };
+        }
+        writer.write(code);
+    }
+    writer
+}
+
+/// Returns, for every word of the stream, whether it holds bits of a tainted
+/// code.
+fn tainted_words(input: &Input, codes: &[Code], words: usize) -> Vec<bool> {
+    let mut tainted = vec![false; words];
+    let mut pos = 0;
+    for (&symbol, &symbol_tainted) in input.symbols.iter().zip(&input.tainted) {
+        let len = codes[symbol as usize].len;
+        if symbol_tainted {
+            tainted[pos / WORD_BITS..=(pos + len - 1) / WORD_BITS].fill(true);
+        }
+        pos += len;
+    }
+    tainted
+}
+
+fn benchmark(n: usize) -> i32 {
+    let input = setup_input(n);
+    if input.symbols.is_empty() {
+        return 0;
+    }
+    let tree = Tree::new(&input.symbols);
+    let codes = tree.codes();
+    let stream = encode(&input, &codes);
+
+    let tainted = tainted_words(&input, &codes, stream.words.len());
+    for (&word, &word_tainted) in stream.words.iter().zip(&tainted) {
+        if word_tainted {
+            unsafe { assert_is_tainted_i32(word as i32) };
+        } else {
+            unsafe { assert_is_not_tainted_i32(word as i32) };
+        }
+    }
+
+    let mut reader = BitReader {
+        words: &stream.words,
+        pos: 0,
+    };
+    let mut tainted_bits = 0;
+    for &symbol in &input.symbols {
+        let mut node = tree.root;
+        while let Node::Internal(children) = tree.nodes[node] {
+            let word_tainted = tainted[reader.pos / WORD_BITS];
+            let bit = reader.read();
+            if word_tainted {
+                unsafe { assert_is_tainted_i32(bit as i32) };
+                tainted_bits += 1;
+            } else {
+                unsafe { assert_is_not_tainted_i32(bit as i32) };
+            }
+            node = children[bit as usize];
+        }
+
+        let Node::Leaf(decoded) = tree.nodes[node] else {
+            unreachable!()
+        };
+        assert_eq!(decoded, symbol);
+        unsafe { assert_is_not_tainted_i32(decoded as i32) };
+    }
+    assert_eq!(reader.pos, stream.len);
+
+    tainted_bits
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
@@ -0,0 +1,140 @@
+import fs from "fs";
+
+const Taint = globalThis.Taint;
+
+const JSImport = {
+    input_length: () => {
+        throw new Error("input_length not implemented");
+    },
+    read_input_byte: () => {
+        throw new Error("read_input_byte not implemented");
+    },
+    write_output_byte: () => {
+        throw new Error("write_output_byte not implemented");
+    },
+};
+
+const LABELS = ["taint", "café", "naïve", "Zürich", "flow", "sink"];
+const KEY_COUNT = 7;
+
+// JSON text as UTF-8 bytes, remembering which bytes were tainted.
+class Input {
+    constructor() {
+        this.encoder = new TextEncoder();
+        this.bytes = [];
+        this.tainted = [];
+    }
+
+    push(text, tainted) {
+        for (const byte of this.encoder.encode(text)) {
+            this.bytes.push(byte);
+            this.tainted.push(tainted);
+        }
+    }
+
+    pushClean(text) {
+        this.push(text, false);
+    }
+
+    pushTainted(text) {
+        this.push(text, true);
+    }
+
+    // Only the digits are tainted: the sign and decimal point are emitted as
+    // constants by the serializer.
+    pushTaintedNumber(x) {
+        for (const ch of String(x)) {
+            this.push(ch, ch >= "0" && ch <= "9");
+        }
+    }
+}
+
+function recordLabel(i) {
+    return `${LABELS[i % LABELS.length]}-${i}`;
+}
+
+function recordWeight(i) {
+    const weight = ((i * 37) % 1000) + 1 + (2 * (i % 4) + 1) / 8;
+    return i % 5 === 0 ? -weight : weight;
+}
+
+function recordKey(i) {
+    return `attr${i % KEY_COUNT}`;
+}
+
+function setupInput(n) {
+    const input = new Input();
+    input.pushClean('{"records":[');
+    for (let i = 0; i < n; i++) {
+        if (i > 0) {
+            input.pushClean(",");
+        }
+        input.pushClean(`{"id":${i},"label":"`);
+        input.pushTainted(recordLabel(i));
+        input.pushClean('","weight":');
+        input.pushTaintedNumber(recordWeight(i));
+        input.pushClean(',"');
+        input.pushTainted(recordKey(i));
+        input.pushClean('":[true,false,null,"tab\\tand \\u0001"],"nested":{"depth":');
+        input.pushClean(`${i % 3},"empty":[]}}`);
+    }
+    input.pushClean(`],"count":${n}}`);
+    return input;
+}
+
+function readInputByte(input, index) {
+    const byte = input.bytes[index];
+    return input.tainted[index] ? Taint.source(byte) : byte;
+}
+
+function checkOutput(output, input) {
+    if (output.length !== input.bytes.length) {
+        throw new Error(`Output length ${output.length} does not match input length ${input.bytes.length}`);
+    }
+    for (let i = 0; i < output.length; i++) {
+        if (output[i] !== input.bytes[i]) {
+            throw new Error(`Output byte ${i} does not match the input`);
+        }
+        if (input.tainted[i]) {
+            Taint.assertIsTainted(output[i]);
+        } else {
+            Taint.assertIsNotTainted(output[i]);
+        }
+    }
+}
+
+export default async function main(
+    insturmentedWasmPath,
+    iterations,
+    additionalImportObject,
+    additionalImportObjectFillerFunction
+) {
+    const wasmBuffer = fs.readFileSync(insturmentedWasmPath);
+
+    const jsMethods = Object.keys(JSImport).reduce((methods, key) => {
+        methods[key] = (...args) => JSImport[key](...args);
+        return methods;
+    }, {});
+
+    const module = await WebAssembly.instantiate(wasmBuffer, {
+        js: jsMethods,
+        ...additionalImportObject,
+    });
+
+    const input = setupInput(iterations);
+    const output = [];
+    JSImport.input_length = () => input.bytes.length;
+    JSImport.read_input_byte = (index) => readInputByte(input, index);
+    JSImport.write_output_byte = (byte) => {
+        output.push(byte);
+    };
+
+    if (additionalImportObjectFillerFunction) {
+        additionalImportObjectFillerFunction(module.instance.exports);
+    }
+
+    const wasmMain = module.instance.exports.main;
+    const res = wasmMain(iterations);
+    checkOutput(output, input);
+    return res;
+}
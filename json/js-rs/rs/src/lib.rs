@@ -0,0 +1,374 @@
+// JSON benchmark
+// A hand-written JSON parser producing a value tree, and a serializer writing it
+// back out. String values, object keys and number digits in the input text are
+// tainted, so taint has to survive `str` to `f64` conversion, map keys and
+// re-serialization, down to the individual output bytes. The JSON text is
+// generated and tainted in JavaScript and passed across the boundary as UTF-8
+// bytes; the serialized output is handed back to JavaScript byte by byte.
+
+use std::io::Write;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_tainted_f64(val: f64);
+    fn assert_is_not_tainted_i32(val: i32);
+    fn assert_is_not_tainted_f64(val: f64);
+}
+
+#[link(wasm_import_module = "js")]
+unsafe extern "C" {
+    fn input_length() -> i32;
+    fn read_input_byte(index: i32) -> i32;
+    fn write_output_byte(byte: i32);
+}
+
+const LABELS: [&str; 6] = ["taint", "café", "naïve", "Zürich", "flow", "sink"];
+const KEY_COUNT: usize = 7;
+
+enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn as_array(&self) -> &[Value] {
+        match self {
+            Value::Array(items) => items,
+            _ => panic!("Expected an array"),
+        }
+    }
+
+    fn as_object(&self) -> &[(String, Value)] {
+        match self {
+            Value::Object(fields) => fields,
+            _ => panic!("Expected an object"),
+        }
+    }
+
+    fn as_number(&self) -> f64 {
+        match self {
+            Value::Number(x) => *x,
+            _ => panic!("Expected a number"),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Value::String(s) => s,
+            _ => panic!("Expected a string"),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(input: &'a [u8]) -> Value {
+        let mut parser = Parser { input, pos: 0 };
+        let value = parser.parse_value();
+        parser.skip_whitespace();
+        assert_eq!(parser.pos, input.len(), "Trailing characters");
+        value
+    }
+
+    fn peek(&self) -> u8 {
+        self.input[self.pos]
+    }
+
+    fn next(&mut self) -> u8 {
+        let byte = self.input[self.pos];
+        self.pos += 1;
+        byte
+    }
+
+    fn expect(&mut self, expected: u8) {
+        let byte = self.next();
+        assert_eq!(byte, expected, "Unexpected character at {}", self.pos - 1);
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.input.len() && matches!(self.peek(), b' ' | b'\t' | b'\n' | b'\r') {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Value {
+        self.skip_whitespace();
+        match self.peek() {
+            b'{' => self.parse_object(),
+            b'[' => self.parse_array(),
+            b'"' => Value::String(self.parse_string()),
+            b't' => self.parse_literal("true", Value::Bool(true)),
+            b'f' => self.parse_literal("false", Value::Bool(false)),
+            b'n' => self.parse_literal("null", Value::Null),
+            _ => Value::Number(self.parse_number()),
+        }
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: Value) -> Value {
+        for &byte in literal.as_bytes() {
+            self.expect(byte);
+        }
+        value
+    }
+
+    fn parse_object(&mut self) -> Value {
+        let mut fields = Vec::new();
+        self.expect(b'{');
+        self.skip_whitespace();
+        if self.peek() == b'}' {
+            self.pos += 1;
+            return Value::Object(fields);
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string();
+            self.skip_whitespace();
+            self.expect(b':');
+            fields.push((key, self.parse_value()));
+            self.skip_whitespace();
+            match self.next() {
+                b',' => continue,
+                b'}' => return Value::Object(fields),
+                byte => panic!("Unexpected character in object: {}", byte as char),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Value {
+        let mut items = Vec::new();
+        self.expect(b'[');
+        self.skip_whitespace();
+        if self.peek() == b']' {
+            self.pos += 1;
+            return Value::Array(items);
+        }
+
+        loop {
+            items.push(self.parse_value());
+            self.skip_whitespace();
+            match self.next() {
+                b',' => continue,
+                b']' => return Value::Array(items),
+                byte => panic!("Unexpected character in array: {}", byte as char),
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> u32 {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = (self.next() as char)
+                .to_digit(16)
+                .expect("Invalid \\u escape");
+            code = code * 16 + digit;
+        }
+        code
+    }
+
+    fn parse_string(&mut self) -> String {
+        let mut bytes = Vec::new();
+        self.expect(b'"');
+        loop {
+            match self.next() {
+                b'"' => break,
+                b'\\' => match self.next() {
+                    b'"' => bytes.push(b'"'),
+                    b'\\' => bytes.push(b'\\'),
+                    b'/' => bytes.push(b'/'),
+                    b'b' => bytes.push(0x08),
+                    b'f' => bytes.push(0x0c),
+                    b'n' => bytes.push(b'\n'),
+                    b'r' => bytes.push(b'\r'),
+                    b't' => bytes.push(b'\t'),
+                    b'u' => {
+                        let ch = char::from_u32(self.parse_hex4()).expect("Invalid code point");
+                        let mut buf = [0; 4];
+                        bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+                    }
+                    byte => panic!("Invalid escape: \\{}", byte as char),
+                },
+                byte => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).expect("Invalid UTF-8 in string")
+    }
+
+    fn parse_number(&mut self) -> f64 {
+        let start = self.pos;
+        while self.pos < self.input.len()
+            && matches!(self.peek(), b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.input[start..self.pos]).unwrap();
+        text.parse::<f64>().expect("Invalid number")
+    }
+}
+
+fn serialize_string(s: &str, out: &mut Vec<u8>) {
+    out.push(b'"');
+    for &byte in s.as_bytes() {
+        match byte {
+            b'"' => out.extend_from_slice(b"\\\""),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            0x08 => out.extend_from_slice(b"\\b"),
+            0x0c => out.extend_from_slice(b"\\f"),
+            0x00..=0x1f => write!(out, "\\u{:04x}", byte).unwrap(),
+            _ => out.push(byte),
+        }
+    }
+    out.push(b'"');
+}
+
+fn serialize(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.extend_from_slice(b"null"),
+        Value::Bool(true) => out.extend_from_slice(b"true"),
+        Value::Bool(false) => out.extend_from_slice(b"false"),
+        Value::Number(x) => write!(out, "{}", x).unwrap(),
+        Value::String(s) => serialize_string(s, out),
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                serialize(item, out);
+            }
+            out.push(b']');
+        }
+        Value::Object(fields) => {
+            out.push(b'{');
+            for (i, (key, value)) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                serialize_string(key, out);
+                out.push(b':');
+                serialize(value, out);
+            }
+            out.push(b'}');
+        }
+    }
+}
+
+fn record_label(i: usize) -> String {
+    format!("{}-{}", LABELS[i % LABELS.len()], i)
+}
+
+/// Always has a fractional part, so the shortest representation printed by the
+/// serializer consists of computed digits only.
+fn record_weight(i: usize) -> f64 {
+    let weight = ((i * 37) % 1000 + 1) as f64 + (2 * (i % 4) + 1) as f64 / 8.0;
+    if i.is_multiple_of(5) { -weight } else { weight }
+}
+
+fn record_key(i: usize) -> String {
+    format!("attr{}", i % KEY_COUNT)
+}
+
+/// Reads the JSON text generated by JavaScript, one UTF-8 byte at a time.
+fn read_input() -> Vec<u8> {
+    let len = unsafe { input_length() };
+    (0..len)
+        .map(|i| unsafe { read_input_byte(i) } as u8)
+        .collect()
+}
+
+fn assert_str_tainted(s: &str) {
+    for &byte in s.as_bytes() {
+        unsafe { assert_is_tainted_i32(byte as i32) };
+    }
+}
+
+fn assert_str_not_tainted(s: &str) {
+    for &byte in s.as_bytes() {
+        unsafe { assert_is_not_tainted_i32(byte as i32) };
+    }
+}
+
+/// Checks the parsed tree against the generator and returns the sum of all
+/// record weights.
+fn check_records(root: &Value, n: usize) -> f64 {
+    let root = root.as_object();
+    assert_eq!(root.len(), 2);
+    assert_eq!(root[1].0, "count");
+    assert_eq!(root[1].1.as_number(), n as f64);
+    unsafe { assert_is_not_tainted_f64(root[1].1.as_number()) };
+
+    let records = root[0].1.as_array();
+    assert_eq!(records.len(), n);
+
+    let mut weight_sum = 0.0;
+    for (i, record) in records.iter().enumerate() {
+        let fields = record.as_object();
+        assert_eq!(fields.len(), 5);
+
+        let id = fields[0].1.as_number();
+        assert_eq!(id, i as f64);
+        unsafe { assert_is_not_tainted_f64(id) };
+
+        let label = fields[1].1.as_str();
+        assert_eq!(label, record_label(i));
+        assert_str_not_tainted(&fields[1].0);
+        assert_str_tainted(label);
+
+        let weight = fields[2].1.as_number();
+        assert_eq!(weight, record_weight(i));
+        unsafe { assert_is_tainted_f64(weight) };
+        weight_sum += weight;
+
+        let (key, flags) = &fields[3];
+        assert_eq!(*key, record_key(i));
+        assert_str_tainted(key);
+        assert_str_not_tainted(flags.as_array()[3].as_str());
+
+        let depth = fields[4].1.as_object()[0].1.as_number();
+        unsafe { assert_is_not_tainted_f64(depth) };
+    }
+
+    weight_sum
+}
+
+/// Checks that re-serialization reproduces the input and hands the output to
+/// JavaScript, which checks the taint of every byte.
+fn write_output(output: &[u8], input: &[u8]) {
+    assert_eq!(output, input);
+    for &byte in output {
+        unsafe { write_output_byte(byte as i32) };
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    let input = read_input();
+
+    let root = Parser::parse(&input);
+    let weight_sum = check_records(&root, n);
+    if n > 0 {
+        unsafe { assert_is_tainted_f64(weight_sum) };
+    }
+
+    let mut output = Vec::with_capacity(input.len());
+    serialize(&root, &mut output);
+    write_output(&output, &input);
+
+    output.len() as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
@@ -0,0 +1,317 @@
+// Linked list benchmark
+// Applies the same random sequence of pushes, pops and rotations at both ends
+// to a `LinkedList`, a `VecDeque` and an intrusive doubly linked list of small
+// heap-allocated nodes, whose items carry a payload tainted for one item out
+// of `TAINTED_PERIOD`. Rotating the intrusive list relinks nodes without
+// moving them, while the other containers move their items.
+//
+// Payloads only move between allocations, so each keeps its taint, which is
+// asserted when it is popped and on periodic traversals of every container.
+
+use std::collections::{LinkedList, VecDeque};
+use std::ptr;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const TAINTED_PERIOD: u32 = 3;
+const MAX_LEN: usize = 4096;
+const MAX_ROTATION: usize = 8;
+const TRAVERSAL_PERIOD: usize = 4096;
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Item {
+    id: u32,
+    payload: i32,
+}
+
+impl Item {
+    fn new(id: u32) -> Self {
+        let payload = (id.wrapping_mul(7) + 1) as i32;
+        Item {
+            id,
+            payload: if Item::is_tainted(id) {
+                unsafe { taint_i32(payload) }
+            } else {
+                payload
+            },
+        }
+    }
+
+    fn is_tainted(id: u32) -> bool {
+        id.is_multiple_of(TAINTED_PERIOD)
+    }
+
+    fn check(&self) {
+        assert_eq!(self.payload, (self.id.wrapping_mul(7) + 1) as i32);
+        if Item::is_tainted(self.id) {
+            unsafe { assert_is_tainted_i32(self.payload) };
+        } else {
+            unsafe { assert_is_not_tainted_i32(self.payload) };
+        }
+    }
+}
+
+struct Node {
+    item: Item,
+    previous: *mut Node,
+    next: *mut Node,
+}
+
+/// Doubly linked list owning its nodes through raw pointers.
+struct IntrusiveList {
+    head: *mut Node,
+    tail: *mut Node,
+    len: usize,
+}
+
+impl IntrusiveList {
+    fn new() -> Self {
+        IntrusiveList {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn push_front(&mut self, item: Item) {
+        let node = Box::into_raw(Box::new(Node {
+            item,
+            previous: ptr::null_mut(),
+            next: self.head,
+        }));
+        self.link_front(node);
+    }
+
+    fn push_back(&mut self, item: Item) {
+        let node = Box::into_raw(Box::new(Node {
+            item,
+            previous: self.tail,
+            next: ptr::null_mut(),
+        }));
+        self.link_back(node);
+    }
+
+    fn pop_front(&mut self) -> Option<Item> {
+        let node = self.unlink_front()?;
+        Some(unsafe { Box::from_raw(node) }.item)
+    }
+
+    fn pop_back(&mut self) -> Option<Item> {
+        let node = self.unlink_back()?;
+        Some(unsafe { Box::from_raw(node) }.item)
+    }
+
+    /// Moves the first `k` nodes to the back, one at a time.
+    fn rotate_left(&mut self, k: usize) {
+        for _ in 0..k {
+            let node = self.unlink_front().unwrap();
+            unsafe {
+                (*node).previous = self.tail;
+                (*node).next = ptr::null_mut();
+            }
+            self.link_back(node);
+        }
+    }
+
+    /// Links `node`, whose `next` is the current head, at the front.
+    fn link_front(&mut self, node: *mut Node) {
+        if self.head.is_null() {
+            self.tail = node;
+        } else {
+            unsafe { (*self.head).previous = node };
+        }
+        self.head = node;
+        self.len += 1;
+    }
+
+    /// Links `node`, whose `previous` is the current tail, at the back.
+    fn link_back(&mut self, node: *mut Node) {
+        if self.tail.is_null() {
+            self.head = node;
+        } else {
+            unsafe { (*self.tail).next = node };
+        }
+        self.tail = node;
+        self.len += 1;
+    }
+
+    fn unlink_front(&mut self) -> Option<*mut Node> {
+        if self.head.is_null() {
+            return None;
+        }
+        let node = self.head;
+        unsafe {
+            self.head = (*node).next;
+            if self.head.is_null() {
+                self.tail = ptr::null_mut();
+            } else {
+                (*self.head).previous = ptr::null_mut();
+            }
+        }
+        self.len -= 1;
+        Some(node)
+    }
+
+    fn unlink_back(&mut self) -> Option<*mut Node> {
+        if self.tail.is_null() {
+            return None;
+        }
+        let node = self.tail;
+        unsafe {
+            self.tail = (*node).previous;
+            if self.tail.is_null() {
+                self.head = ptr::null_mut();
+            } else {
+                (*self.tail).next = ptr::null_mut();
+            }
+        }
+        self.len -= 1;
+        Some(node)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Item> {
+        let mut node = self.head;
+        std::iter::from_fn(move || {
+            if node.is_null() {
+                return None;
+            }
+            let item = unsafe { &(*node).item };
+            node = unsafe { (*node).next };
+            Some(item)
+        })
+    }
+}
+
+impl Drop for IntrusiveList {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+fn rotate_linked_list(list: &mut LinkedList<Item>, k: usize) {
+    let mut tail = list.split_off(k);
+    tail.append(list);
+    *list = tail;
+}
+
+struct Sums {
+    tainted: i32,
+    clean: i32,
+}
+
+impl Sums {
+    fn add(&mut self, item: Item) {
+        item.check();
+        if Item::is_tainted(item.id) {
+            self.tainted = self.tainted.wrapping_add(item.payload);
+        } else {
+            self.clean = self.clean.wrapping_add(item.payload);
+        }
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    let mut rng = Random::new();
+    let mut linked_list = LinkedList::new();
+    let mut deque = VecDeque::new();
+    let mut intrusive = IntrusiveList::new();
+    let mut next_id = 0;
+    let mut sums = Sums {
+        tainted: 0,
+        clean: 0,
+    };
+
+    for op in 0..n {
+        let len = deque.len();
+        let choice = if len >= MAX_LEN {
+            0.6
+        } else {
+            rng.gen_random(1.0)
+        };
+
+        if choice < 0.45 || len == 0 {
+            let item = Item::new(next_id);
+            next_id += 1;
+            if choice < 0.2 {
+                linked_list.push_front(item);
+                deque.push_front(item);
+                intrusive.push_front(item);
+            } else {
+                linked_list.push_back(item);
+                deque.push_back(item);
+                intrusive.push_back(item);
+            }
+        } else if choice < 0.8 {
+            let item = if choice < 0.625 {
+                let item = linked_list.pop_front();
+                assert_eq!(item, deque.pop_front());
+                assert_eq!(item, intrusive.pop_front());
+                item
+            } else {
+                let item = linked_list.pop_back();
+                assert_eq!(item, deque.pop_back());
+                assert_eq!(item, intrusive.pop_back());
+                item
+            };
+            sums.add(item.unwrap());
+        } else {
+            let k = (1 + rng.gen_random(MAX_ROTATION as f64) as usize).min(len);
+            rotate_linked_list(&mut linked_list, k);
+            deque.rotate_left(k);
+            intrusive.rotate_left(k);
+        }
+
+        if op.is_multiple_of(TRAVERSAL_PERIOD) {
+            assert_eq!(intrusive.len, deque.len());
+            for ((a, b), c) in linked_list.iter().zip(&deque).zip(intrusive.iter()) {
+                assert!(a == b && b == c);
+                a.check();
+            }
+        }
+    }
+
+    while let Some(item) = intrusive.pop_front() {
+        assert_eq!(Some(item), linked_list.pop_front());
+        assert_eq!(Some(item), deque.pop_front());
+        sums.add(item);
+    }
+    assert!(linked_list.is_empty() && deque.is_empty());
+    // The first item is tainted, so the sum is as soon as any item was added.
+    if next_id > 0 {
+        unsafe { assert_is_tainted_i32(sums.tainted) };
+    } else {
+        unsafe { assert_is_not_tainted_i32(sums.tainted) };
+    }
+    unsafe { assert_is_not_tainted_i32(sums.clean) };
+
+    next_id as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
@@ -0,0 +1,291 @@
+// LZ77 benchmark
+// A DEFLATE-style LZ77 compressor (hash chains over a sliding window) and its
+// decompressor, round-tripping a partially tainted input. Back-references copy
+// earlier output at computed offsets, so the taint of a decompressed byte is the
+// taint of the input position it was originally copied from.
+//
+// Match candidates are loaded from hash chains indexed by hashes of tainted
+// bytes, and match lengths are counted by comparing them, so offsets and
+// lengths only depend on tainted bytes through addresses and control flow and
+// are not tainted.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const WINDOW_SIZE: usize = 4096;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 32;
+const HASH_BITS: usize = 12;
+const HASH_SIZE: usize = 1 << HASH_BITS;
+
+const LITERAL_TAG: u8 = 0x00;
+const MATCH_TAG: u8 = 0x01;
+
+// Every third block of TAINT_BLOCK input bytes is tainted.
+const TAINT_BLOCK: usize = 100;
+
+const WORDS: [&str; 8] = [
+    "GGCCGGGCGCGGTGGCTCACGCC",
+    "TGTAATCCCAGCACTTTGG",
+    "GAGGCCGAGG",
+    "CGGGCGGATCACCTGAGG",
+    "TCAGGAGTTCGAGACCAGCC",
+    "TGGCCAACATGGTGAAACCCC",
+    "GTCTCTACTAAAAAT",
+    "ACAAAAATTAGCCGGG",
+];
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+fn should_be_tainted(index: usize) -> bool {
+    (index / TAINT_BLOCK).is_multiple_of(3)
+}
+
+/// Builds `n` bytes of repetitive text out of randomly picked words, tainting
+/// every byte for which `should_be_tainted` holds.
+fn setup_input(n: usize) -> Vec<u8> {
+    let mut rng = Random::new();
+    let mut input = Vec::with_capacity(n);
+
+    while input.len() < n {
+        let word = WORDS[rng.gen_random(WORDS.len() as f64) as usize];
+        for &byte in word.as_bytes().iter().chain(b"\n") {
+            if input.len() == n {
+                break;
+            }
+            if should_be_tainted(input.len()) {
+                input.push(unsafe { taint_i32(byte as i32) } as u8);
+            } else {
+                input.push(byte);
+            }
+        }
+    }
+
+    input
+}
+
+fn hash(input: &[u8], i: usize) -> usize {
+    let h = ((input[i] as usize) << 8) ^ ((input[i + 1] as usize) << 4) ^ input[i + 2] as usize;
+    h & (HASH_SIZE - 1)
+}
+
+fn match_length(input: &[u8], candidate: usize, i: usize) -> usize {
+    let mut length = 0;
+    while length < MAX_MATCH
+        && i + length < input.len()
+        && input[candidate + length] == input[i + length]
+    {
+        length += 1;
+    }
+    length
+}
+
+struct HashChains {
+    head: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl HashChains {
+    fn new(len: usize) -> Self {
+        HashChains {
+            head: vec![usize::MAX; HASH_SIZE],
+            prev: vec![usize::MAX; len],
+        }
+    }
+
+    fn insert(&mut self, input: &[u8], i: usize) {
+        if i + MIN_MATCH <= input.len() {
+            let h = hash(input, i);
+            self.prev[i] = self.head[h];
+            self.head[h] = i;
+        }
+    }
+
+    /// Returns the `(offset, length)` of the longest match for position `i`.
+    fn longest_match(&self, input: &[u8], i: usize) -> (usize, usize) {
+        let mut best = (0, 0);
+        if i + MIN_MATCH > input.len() {
+            return best;
+        }
+
+        let mut candidate = self.head[hash(input, i)];
+        let mut chain = 0;
+        while candidate != usize::MAX && i - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+            let length = match_length(input, candidate, i);
+            if length > best.1 {
+                best = (i - candidate, length);
+                if length == MAX_MATCH {
+                    break;
+                }
+            }
+            candidate = self.prev[candidate];
+            chain += 1;
+        }
+
+        best
+    }
+}
+
+/// Encodes `input` as a stream of literal (`LITERAL_TAG`, byte) and match
+/// (`MATCH_TAG`, offset as u16 LE, length - `MIN_MATCH`) tokens.
+fn compress(input: &[u8]) -> Vec<u8> {
+    let mut chains = HashChains::new(input.len());
+    let mut stream = Vec::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < input.len() {
+        let (offset, length) = chains.longest_match(input, i);
+        if length >= MIN_MATCH {
+            stream.push(MATCH_TAG);
+            stream.extend_from_slice(&(offset as u16).to_le_bytes());
+            stream.push((length - MIN_MATCH) as u8);
+            for j in i..i + length {
+                chains.insert(input, j);
+            }
+            i += length;
+        } else {
+            stream.push(LITERAL_TAG);
+            stream.push(input[i]);
+            chains.insert(input, i);
+            i += 1;
+        }
+    }
+
+    stream
+}
+
+fn read_match(stream: &[u8], pos: usize) -> (usize, usize) {
+    let offset = u16::from_le_bytes([stream[pos + 1], stream[pos + 2]]) as usize;
+    let length = stream[pos + 3] as usize + MIN_MATCH;
+    (offset, length)
+}
+
+fn decompress(stream: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(stream.len() * 2);
+    let mut pos = 0;
+
+    while pos < stream.len() {
+        match stream[pos] {
+            LITERAL_TAG => {
+                output.push(stream[pos + 1]);
+                pos += 2;
+            }
+            MATCH_TAG => {
+                let (offset, length) = read_match(stream, pos);
+                let start = output.len() - offset;
+                if offset >= length {
+                    output.extend_from_within(start..start + length);
+                } else {
+                    // Overlapping copy: the match repeats bytes it is producing.
+                    for k in 0..length {
+                        let byte = output[start + k];
+                        output.push(byte);
+                    }
+                }
+                pos += 4;
+            }
+            tag => panic!("Unknown token tag: {}", tag),
+        }
+    }
+
+    output
+}
+
+/// Returns, for every decompressed byte, the input position of the literal it
+/// was ultimately copied from.
+fn resolve_origins(stream: &[u8]) -> Vec<usize> {
+    let mut origins: Vec<usize> = Vec::with_capacity(stream.len() * 2);
+    let mut pos = 0;
+
+    while pos < stream.len() {
+        if stream[pos] == LITERAL_TAG {
+            origins.push(origins.len());
+            pos += 2;
+        } else {
+            let (offset, length) = read_match(stream, pos);
+            let start = origins.len() - offset;
+            for k in 0..length {
+                origins.push(origins[start + k]);
+            }
+            pos += 4;
+        }
+    }
+
+    origins
+}
+
+/// Asserts that literals are tainted iff their input position is, and that
+/// match tokens are not tainted.
+fn check_stream(stream: &[u8]) {
+    let mut pos = 0;
+    let mut len = 0;
+    while pos < stream.len() {
+        if stream[pos] == LITERAL_TAG {
+            let literal = stream[pos + 1] as i32;
+            if should_be_tainted(len) {
+                unsafe { assert_is_tainted_i32(literal) };
+            } else {
+                unsafe { assert_is_not_tainted_i32(literal) };
+            }
+            len += 1;
+            pos += 2;
+        } else {
+            for &byte in &stream[pos + 1..pos + 4] {
+                unsafe { assert_is_not_tainted_i32(byte as i32) };
+            }
+            len += read_match(stream, pos).1;
+            pos += 4;
+        }
+    }
+}
+
+fn check_decompressed(output: &[u8], origins: &[usize]) {
+    for (&byte, &origin) in output.iter().zip(origins) {
+        if should_be_tainted(origin) {
+            unsafe { assert_is_tainted_i32(byte as i32) };
+        } else {
+            unsafe { assert_is_not_tainted_i32(byte as i32) };
+        }
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    let input = setup_input(n);
+
+    let stream = compress(&input);
+    check_stream(&stream);
+    let output = decompress(&stream);
+    assert_eq!(output, input);
+
+    let origins = resolve_origins(&stream);
+    check_decompressed(&output, &origins);
+
+    stream.len() as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
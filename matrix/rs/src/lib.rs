@@ -0,0 +1,250 @@
+// Matrix benchmark
+// Multiplies dense row-major matrices and factors one with LU decomposition
+// with partial pivoting, then solves a linear system with the factors. One row
+// of the matrix `A` is tainted.
+//
+// In `A * B`, only the row computed from the tainted row is tainted, while
+// every entry of `B * A` sums a product with one of its entries. Pivot choices
+// compare tainted values, but only decide through control flow which row is
+// swapped, so the permutation is not tainted. If the tainted row becomes the
+// pivot row at step `s`, it taints the first `s` multipliers of its own row,
+// and at step `s` every multiplier and every entry left to eliminate.
+
+use std::ops::{Index, IndexMut};
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_f64(val: f64) -> f64;
+    fn assert_is_tainted_f64(val: f64);
+    fn assert_is_not_tainted_i32(val: i32);
+    fn assert_is_not_tainted_f64(val: f64);
+}
+
+const TOLERANCE: f64 = 1e-9;
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+#[derive(Clone)]
+struct Matrix {
+    n: usize,
+    data: Vec<f64>,
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        &self.data[i * self.n + j]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        &mut self.data[i * self.n + j]
+    }
+}
+
+impl Matrix {
+    fn random(n: usize, rng: &mut Random) -> Self {
+        Matrix {
+            n,
+            data: (0..n * n).map(|_| rng.gen_random(2.0) - 1.0).collect(),
+        }
+    }
+
+    fn row(&self, i: usize) -> &[f64] {
+        &self.data[i * self.n..(i + 1) * self.n]
+    }
+
+    fn row_mut(&mut self, i: usize) -> &mut [f64] {
+        &mut self.data[i * self.n..(i + 1) * self.n]
+    }
+
+    /// Computes each entry as the dot product of a row of `self` with a
+    /// column of `other`, read with a stride of `n`.
+    fn mul(&self, other: &Matrix) -> Matrix {
+        let n = self.n;
+        let mut product = Matrix {
+            n,
+            data: vec![0.0; n * n],
+        };
+        for i in 0..n {
+            for j in 0..n {
+                let mut sum = 0.0;
+                for k in 0..n {
+                    sum += self[(i, k)] * other[(k, j)];
+                }
+                product[(i, j)] = sum;
+            }
+        }
+        product
+    }
+
+    fn mul_vec(&self, x: &[f64]) -> Vec<f64> {
+        (0..self.n)
+            .map(|i| self.row(i).iter().zip(x).map(|(a, b)| a * b).sum())
+            .collect()
+    }
+}
+
+/// LU decomposition of `P * A`, with the multipliers of `L` stored below the
+/// diagonal, `U` on and above it, and row `i` of `P * A` being row `perm[i]`
+/// of `A`.
+struct Lu {
+    factors: Matrix,
+    perm: Vec<usize>,
+}
+
+impl Lu {
+    fn new(a: &Matrix) -> Self {
+        let n = a.n;
+        let mut factors = a.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+
+        for k in 0..n {
+            let pivot = (k..n)
+                .max_by(|&p, &q| factors[(p, k)].abs().total_cmp(&factors[(q, k)].abs()))
+                .unwrap();
+            if pivot != k {
+                perm.swap(k, pivot);
+                for j in 0..n {
+                    factors.data.swap(k * n + j, pivot * n + j);
+                }
+            }
+
+            let (above, below) = factors.data.split_at_mut((k + 1) * n);
+            let pivot_row = &above[k * n..];
+            for row in below.chunks_mut(n) {
+                let multiplier = row[k] / pivot_row[k];
+                row[k] = multiplier;
+                for j in k + 1..n {
+                    row[j] -= multiplier * pivot_row[j];
+                }
+            }
+        }
+
+        Lu { factors, perm }
+    }
+
+    fn l(&self) -> Matrix {
+        let n = self.factors.n;
+        let mut l = self.factors.clone();
+        for i in 0..n {
+            for j in i..n {
+                l[(i, j)] = if i == j { 1.0 } else { 0.0 };
+            }
+        }
+        l
+    }
+
+    fn u(&self) -> Matrix {
+        let n = self.factors.n;
+        let mut u = self.factors.clone();
+        for i in 0..n {
+            for j in 0..i {
+                u[(i, j)] = 0.0;
+            }
+        }
+        u
+    }
+
+    /// Solves `A * x = b` by forward and back substitution.
+    fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.factors.n;
+        let mut x: Vec<f64> = self.perm.iter().map(|&p| b[p]).collect();
+        for i in 0..n {
+            for j in 0..i {
+                x[i] -= self.factors[(i, j)] * x[j];
+            }
+        }
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                x[i] -= self.factors[(i, j)] * x[j];
+            }
+            x[i] /= self.factors[(i, i)];
+        }
+        x
+    }
+}
+
+fn assert_taint(values: &[f64], tainted: impl Fn(usize) -> bool) {
+    for (i, &value) in values.iter().enumerate() {
+        if tainted(i) {
+            unsafe { assert_is_tainted_f64(value) };
+        } else {
+            unsafe { assert_is_not_tainted_f64(value) };
+        }
+    }
+}
+
+fn assert_close(a: &[f64], b: &[f64]) {
+    for (x, y) in a.iter().zip(b) {
+        assert!((x - y).abs() < TOLERANCE);
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    // An empty matrix has no row to taint.
+    if n == 0 {
+        return 0;
+    }
+    let mut rng = Random::new();
+    let mut a = Matrix::random(n, &mut rng);
+    let b = Matrix::random(n, &mut rng);
+    let tainted_row = n / 3;
+    for value in a.row_mut(tainted_row) {
+        *value = unsafe { taint_f64(*value) };
+    }
+
+    let ab = a.mul(&b);
+    assert_taint(&ab.data, |index| index / n == tainted_row);
+    let ba = b.mul(&a);
+    assert_taint(&ba.data, |_| true);
+
+    let lu = Lu::new(&a);
+    for &p in &lu.perm {
+        unsafe { assert_is_not_tainted_i32(p as i32) };
+    }
+    let step = lu.perm.iter().position(|&p| p == tainted_row).unwrap();
+    unsafe { assert_is_not_tainted_i32(step as i32) };
+    assert_taint(&lu.factors.data, |index| {
+        let (i, j) = (index / n, index % n);
+        if j < i {
+            i == step || j >= step
+        } else {
+            i >= step
+        }
+    });
+
+    let permuted: Vec<f64> = lu.perm.iter().flat_map(|&p| a.row(p).to_vec()).collect();
+    assert_close(&lu.l().mul(&lu.u()).data, &permuted);
+
+    let rhs: Vec<f64> = (0..n).map(|i| i as f64).collect();
+    let x = lu.solve(&rhs);
+    assert_taint(&x, |_| true);
+    assert_close(&a.mul_vec(&x), &rhs);
+
+    step as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
@@ -0,0 +1,170 @@
+// PRNG benchmark
+// Seeds MT19937 and xorshift64* generators with a tainted seed. Every word of
+// the Mersenne Twister state is derived from the seed and updated in place by
+// each twist, so the whole state array and every generated value are tainted.
+// The outputs drive a Monte Carlo estimation of pi, whose estimate is tainted
+// as well. A generator with an untainted seed is run alongside as a control.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn taint_i64(val: i64) -> i64;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_tainted_i64(val: i64);
+    fn assert_is_tainted_f64(val: f64);
+    fn assert_is_not_tainted_i32(val: i32);
+    fn assert_is_not_tainted_f64(val: f64);
+}
+
+const MT_N: usize = 624;
+const MT_M: usize = 397;
+const MT_MATRIX_A: u32 = 0x9908b0df;
+const MT_UPPER_MASK: u32 = 0x80000000;
+const MT_LOWER_MASK: u32 = 0x7fffffff;
+const MT_DEFAULT_SEED: u32 = 5489;
+// Output number 10000 of MT19937 seeded with the default seed.
+const MT_10000TH_OUTPUT: u32 = 4123659995;
+
+const XORSHIFT_SEED: u64 = 0x9e3779b97f4a7c15;
+// The estimates of pi are only checked once there are enough samples for them
+// to reliably fall within the tolerance.
+const PI_TOLERANCE: f64 = 0.05;
+const PI_MIN_SAMPLES: usize = 10_000;
+
+trait Generator {
+    fn next_u32(&mut self) -> u32;
+
+    /// Returns a value uniformly distributed in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        self.next_u32() as f64 / (1u64 << 32) as f64
+    }
+}
+
+struct Mt19937 {
+    state: [u32; MT_N],
+    index: usize,
+}
+
+impl Mt19937 {
+    fn new(seed: u32) -> Self {
+        let mut state = [0; MT_N];
+        state[0] = seed;
+        for i in 1..MT_N {
+            let prev = state[i - 1];
+            state[i] = 1812433253u32
+                .wrapping_mul(prev ^ (prev >> 30))
+                .wrapping_add(i as u32);
+        }
+        Mt19937 { state, index: MT_N }
+    }
+
+    /// Regenerates the whole state array in place.
+    fn twist(&mut self) {
+        for i in 0..MT_N {
+            let y = (self.state[i] & MT_UPPER_MASK) | (self.state[(i + 1) % MT_N] & MT_LOWER_MASK);
+            // The low bit of `y` selects the matrix without branching.
+            let mag = (y & 1).wrapping_neg() & MT_MATRIX_A;
+            self.state[i] = self.state[(i + MT_M) % MT_N] ^ (y >> 1) ^ mag;
+        }
+        self.index = 0;
+    }
+}
+
+impl Generator for Mt19937 {
+    fn next_u32(&mut self) -> u32 {
+        if self.index >= MT_N {
+            self.twist();
+        }
+        let mut y = self.state[self.index];
+        self.index += 1;
+
+        y ^= y >> 11;
+        y ^= (y << 7) & 0x9d2c5680;
+        y ^= (y << 15) & 0xefc60000;
+        y ^ (y >> 18)
+    }
+}
+
+struct XorShift64Star {
+    state: u64,
+}
+
+impl Generator for XorShift64Star {
+    fn next_u32(&mut self) -> u32 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        (self.state.wrapping_mul(0x2545f4914f6cdd1d) >> 32) as u32
+    }
+}
+
+/// Estimates pi from the fraction of random points of the unit square that lie
+/// within the unit circle.
+fn estimate_pi(rng: &mut impl Generator, samples: usize) -> f64 {
+    let mut inside = 0u32;
+    for _ in 0..samples {
+        let x = rng.next_f64();
+        let y = rng.next_f64();
+        inside += (x * x + y * y < 1.0) as u32;
+    }
+    4.0 * inside as f64 / samples as f64
+}
+
+fn check_mt_state(mt: &Mt19937) {
+    for &word in &mt.state {
+        unsafe { assert_is_tainted_i32(word as i32) };
+    }
+}
+
+fn benchmark(samples: usize) -> i32 {
+    let mut mt = Mt19937::new(unsafe { taint_i32(MT_DEFAULT_SEED as i32) as u32 });
+    check_mt_state(&mt);
+    for i in 1..=10000 {
+        let value = mt.next_u32();
+        unsafe { assert_is_tainted_i32(value as i32) };
+        if i == 10000 {
+            assert_eq!(value, MT_10000TH_OUTPUT);
+        }
+    }
+    check_mt_state(&mt);
+
+    let mut xorshift = XorShift64Star {
+        state: unsafe { taint_i64(XORSHIFT_SEED as i64) as u64 },
+    };
+    for _ in 0..MT_N {
+        let value = xorshift.next_u32();
+        unsafe { assert_is_tainted_i32(value as i32) };
+    }
+    unsafe { assert_is_tainted_i64(xorshift.state as i64) };
+
+    let mut clean = Mt19937::new(MT_DEFAULT_SEED);
+    let mt_pi = estimate_pi(&mut mt, samples);
+    let xorshift_pi = estimate_pi(&mut xorshift, samples);
+    let clean_pi = estimate_pi(&mut clean, samples);
+    check_mt_state(&mt);
+    for &word in &clean.state {
+        unsafe { assert_is_not_tainted_i32(word as i32) };
+    }
+
+    if samples >= PI_MIN_SAMPLES {
+        for pi in [mt_pi, xorshift_pi, clean_pi] {
+            assert!((pi - std::f64::consts::PI).abs() < PI_TOLERANCE);
+        }
+    }
+    // Without samples, the estimates only divide constants.
+    if samples > 0 {
+        unsafe { assert_is_tainted_f64(mt_pi) };
+        unsafe { assert_is_tainted_f64(xorshift_pi) };
+    } else {
+        unsafe { assert_is_not_tainted_f64(mt_pi) };
+        unsafe { assert_is_not_tainted_f64(xorshift_pi) };
+    }
+    unsafe { assert_is_not_tainted_f64(clean_pi) };
+
+    (mt_pi * 1e6) as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
@@ -0,0 +1,334 @@
+// Ray tracer benchmark
+// Renders spheres and a checkered floor lit by a point light into an RGB `u8`
+// framebuffer. One sphere has a tainted position and another a tainted material
+// color, so exactly the pixels whose primary ray hits one of these two spheres
+// are tainted. Shadows cast by the tainted sphere only decide branches.
+
+use std::ops::{Add, Mul, Sub};
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_f64(val: f64) -> f64;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const EPSILON: f64 = 1e-6;
+const AMBIENT: f64 = 0.2;
+const SHADOW: f64 = 0.5;
+const FIELD_OF_VIEW: f64 = 1.0;
+
+const TAINTED_POSITION_SPHERE: usize = 0;
+const TAINTED_COLOR_SPHERE: usize = 1;
+
+#[derive(Clone, Copy)]
+struct Vec3 {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Vec3 {
+    const fn new(x: f64, y: f64, z: f64) -> Self {
+        Vec3 { x, y, z }
+    }
+
+    fn dot(self, other: Vec3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    fn normalize(self) -> Vec3 {
+        self * (1.0 / self.length())
+    }
+
+    fn taint(self) -> Vec3 {
+        unsafe { Vec3::new(taint_f64(self.x), taint_f64(self.y), taint_f64(self.z)) }
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f64> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, s: f64) -> Vec3 {
+        Vec3::new(self.x * s, self.y * s, self.z * s)
+    }
+}
+
+impl Mul<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x * other.x, self.y * other.y, self.z * other.z)
+    }
+}
+
+struct Ray {
+    origin: Vec3,
+    direction: Vec3,
+}
+
+impl Ray {
+    fn at(&self, t: f64) -> Vec3 {
+        self.origin + self.direction * t
+    }
+}
+
+enum Shape {
+    Sphere { center: Vec3, radius: f64 },
+    Plane { point: Vec3, normal: Vec3 },
+}
+
+enum Material {
+    Solid(Vec3),
+    Checker(Vec3, Vec3),
+}
+
+struct Object {
+    shape: Shape,
+    material: Material,
+}
+
+impl Object {
+    /// Returns the distance along `ray` to the nearest intersection in front of
+    /// its origin.
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        match self.shape {
+            Shape::Sphere { center, radius } => {
+                let oc = ray.origin - center;
+                let b = oc.dot(ray.direction);
+                let c = oc.dot(oc) - radius * radius;
+                let discriminant = b * b - c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let root = discriminant.sqrt();
+                let t = -b - root;
+                if t > EPSILON {
+                    return Some(t);
+                }
+                let t = -b + root;
+                if t > EPSILON { Some(t) } else { None }
+            }
+            Shape::Plane { point, normal } => {
+                let denominator = normal.dot(ray.direction);
+                if denominator.abs() < EPSILON {
+                    return None;
+                }
+                let t = (point - ray.origin).dot(normal) / denominator;
+                if t > EPSILON { Some(t) } else { None }
+            }
+        }
+    }
+
+    fn normal_at(&self, point: Vec3) -> Vec3 {
+        match self.shape {
+            Shape::Sphere { center, radius } => (point - center) * (1.0 / radius),
+            Shape::Plane { normal, .. } => normal,
+        }
+    }
+
+    fn color_at(&self, point: Vec3) -> Vec3 {
+        match self.material {
+            Material::Solid(color) => color,
+            Material::Checker(even, odd) => {
+                if (point.x.floor() as i32 + point.z.floor() as i32) & 1 == 0 {
+                    even
+                } else {
+                    odd
+                }
+            }
+        }
+    }
+}
+
+struct Scene {
+    objects: Vec<Object>,
+    light: Vec3,
+    camera: Vec3,
+    background: Vec3,
+}
+
+impl Scene {
+    /// Builds the scene, tainting the position of the first sphere and the
+    /// color of the second if `tainted` is set.
+    fn new(tainted: bool) -> Self {
+        let mut position = Vec3::new(-1.2, 0.0, 1.0);
+        let mut color = Vec3::new(0.9, 0.3, 0.2);
+        if tainted {
+            position = position.taint();
+            color = color.taint();
+        }
+
+        let objects = vec![
+            Object {
+                shape: Shape::Sphere {
+                    center: position,
+                    radius: 1.0,
+                },
+                material: Material::Solid(Vec3::new(0.2, 0.6, 0.9)),
+            },
+            Object {
+                shape: Shape::Sphere {
+                    center: Vec3::new(1.2, 0.0, 1.5),
+                    radius: 1.0,
+                },
+                material: Material::Solid(color),
+            },
+            Object {
+                shape: Shape::Sphere {
+                    center: Vec3::new(0.2, -0.5, -0.8),
+                    radius: 0.5,
+                },
+                material: Material::Solid(Vec3::new(0.3, 0.9, 0.3)),
+            },
+            Object {
+                shape: Shape::Plane {
+                    point: Vec3::new(0.0, -1.0, 0.0),
+                    normal: Vec3::new(0.0, 1.0, 0.0),
+                },
+                material: Material::Checker(Vec3::new(0.9, 0.9, 0.9), Vec3::new(0.1, 0.1, 0.1)),
+            },
+        ];
+
+        Scene {
+            objects,
+            light: Vec3::new(-3.0, 5.0, -3.0),
+            camera: Vec3::new(0.0, 0.5, -4.0),
+            background: Vec3::new(0.5, 0.7, 1.0),
+        }
+    }
+
+    fn primary_ray(&self, x: usize, y: usize, size: usize) -> Ray {
+        let u = ((x as f64 + 0.5) / size as f64 * 2.0 - 1.0) * FIELD_OF_VIEW;
+        let v = (1.0 - (y as f64 + 0.5) / size as f64 * 2.0) * FIELD_OF_VIEW;
+        Ray {
+            origin: self.camera,
+            direction: Vec3::new(u, v, 1.5).normalize(),
+        }
+    }
+
+    /// Returns the index of the nearest object hit by `ray` and the distance
+    /// to it.
+    fn nearest_hit(&self, ray: &Ray) -> Option<(usize, f64)> {
+        let mut nearest = None;
+        let mut nearest_t = f64::INFINITY;
+        for (i, object) in self.objects.iter().enumerate() {
+            if let Some(t) = object.intersect(ray)
+                && t < nearest_t
+            {
+                nearest = Some(i);
+                nearest_t = t;
+            }
+        }
+        nearest.map(|i| (i, nearest_t))
+    }
+
+    fn is_shadowed(&self, point: Vec3, hit: usize) -> bool {
+        let to_light = self.light - point;
+        let distance = to_light.length();
+        let ray = Ray {
+            origin: point,
+            direction: to_light * (1.0 / distance),
+        };
+        self.objects
+            .iter()
+            .enumerate()
+            .any(|(i, object)| i != hit && object.intersect(&ray).is_some_and(|t| t < distance))
+    }
+
+    /// Shades with half-Lambert lighting, which stays within `[0, 1]` without
+    /// clamping, so the color is always computed from the surface values.
+    fn trace(&self, ray: &Ray) -> Vec3 {
+        let Some((hit, t)) = self.nearest_hit(ray) else {
+            return self.background;
+        };
+
+        let object = &self.objects[hit];
+        let point = ray.at(t);
+        let normal = object.normal_at(point);
+        let light_direction = (self.light - point).normalize();
+        let diffuse = 0.5 + 0.5 * normal.dot(light_direction);
+        let mut intensity = AMBIENT + (1.0 - AMBIENT) * diffuse;
+        if self.is_shadowed(point, hit) {
+            intensity *= SHADOW;
+        }
+        object.color_at(point) * intensity
+    }
+
+    fn render(&self, size: usize) -> Vec<u8> {
+        let mut framebuffer = vec![0u8; size * size * 3];
+        for y in 0..size {
+            for x in 0..size {
+                let color = self.trace(&self.primary_ray(x, y, size));
+                let pixel = (y * size + x) * 3;
+                framebuffer[pixel] = (color.x * 255.0) as u8;
+                framebuffer[pixel + 1] = (color.y * 255.0) as u8;
+                framebuffer[pixel + 2] = (color.z * 255.0) as u8;
+            }
+        }
+        framebuffer
+    }
+}
+
+/// Returns, for every pixel, whether its primary ray hits one of the tainted
+/// spheres, using an untainted copy of the scene.
+fn tainted_mask(size: usize) -> Vec<bool> {
+    let scene = Scene::new(false);
+    let mut mask = Vec::with_capacity(size * size);
+    for y in 0..size {
+        for x in 0..size {
+            let hit = scene.nearest_hit(&scene.primary_ray(x, y, size));
+            mask.push(matches!(
+                hit,
+                Some((TAINTED_POSITION_SPHERE | TAINTED_COLOR_SPHERE, _))
+            ));
+        }
+    }
+    mask
+}
+
+fn benchmark(size: usize) -> i32 {
+    let scene = Scene::new(true);
+    let framebuffer = scene.render(size);
+
+    let mask = tainted_mask(size);
+    let mut tainted_pixels = 0;
+    for (pixel, &tainted) in framebuffer.chunks(3).zip(&mask) {
+        for &channel in pixel {
+            if tainted {
+                unsafe { assert_is_tainted_i32(channel as i32) };
+            } else {
+                unsafe { assert_is_not_tainted_i32(channel as i32) };
+            }
+        }
+        tainted_pixels += tainted as i32;
+    }
+
+    assert_eq!(framebuffer, Scene::new(false).render(size));
+    tainted_pixels
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
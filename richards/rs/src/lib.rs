@@ -0,0 +1,449 @@
+// Richards benchmark
+// Adapted from the Octane JavaScript version of Martin Richards' OS task
+// scheduler simulation. Tasks are `Box<dyn Task>` trait objects, so every task
+// switch goes through `call_indirect`. The worker task fills work packets with
+// tainted payload data, which handler tasks copy into device packets.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const ID_IDLE: usize = 0;
+const ID_WORKER: usize = 1;
+const ID_HANDLER_A: usize = 2;
+const ID_HANDLER_B: usize = 3;
+const ID_DEVICE_A: usize = 4;
+const ID_DEVICE_B: usize = 5;
+
+const KIND_DEVICE: i32 = 0;
+const KIND_WORK: i32 = 1;
+
+const DATA_SIZE: usize = 4;
+
+const STATE_RUNNING: i32 = 0;
+const STATE_RUNNABLE: i32 = 1;
+const STATE_SUSPENDED: i32 = 2;
+const STATE_HELD: i32 = 4;
+const STATE_SUSPENDED_RUNNABLE: i32 = STATE_SUSPENDED | STATE_RUNNABLE;
+const STATE_NOT_HELD: i32 = !STATE_HELD;
+
+const PACKET_COUNT: usize = 8;
+
+struct Packet {
+    link: Option<Box<Packet>>,
+    id: usize,
+    kind: i32,
+    a1: i32,
+    a2: [i32; DATA_SIZE],
+    // Whether the payload has been written with tainted data, for the checks.
+    tainted_payload: bool,
+}
+
+impl Packet {
+    fn new(link: Option<Box<Packet>>, id: usize, kind: i32) -> Box<Packet> {
+        Box::new(Packet {
+            link,
+            id,
+            kind,
+            a1: 0,
+            a2: [0; DATA_SIZE],
+            tainted_payload: false,
+        })
+    }
+
+    /// Appends this packet to the end of `queue`, returning the new head.
+    fn add_to(mut self: Box<Self>, queue: Option<Box<Packet>>) -> Option<Box<Packet>> {
+        self.link = None;
+        match queue {
+            None => Some(self),
+            Some(mut head) => {
+                let mut next = &mut head;
+                while next.link.is_some() {
+                    next = next.link.as_mut().unwrap();
+                }
+                next.link = Some(self);
+                Some(head)
+            }
+        }
+    }
+}
+
+trait Task {
+    /// Runs the task, returning the id of the task to schedule next.
+    fn run(&mut self, scheduler: &mut Scheduler, packet: Option<Box<Packet>>) -> Option<usize>;
+
+    /// Calls `f` for every packet the task is holding on to.
+    fn for_each_packet(&self, _f: &mut dyn FnMut(&Packet)) {}
+}
+
+struct IdleTask {
+    v1: i32,
+    count: i32,
+}
+
+impl Task for IdleTask {
+    fn run(&mut self, scheduler: &mut Scheduler, _packet: Option<Box<Packet>>) -> Option<usize> {
+        self.count -= 1;
+        if self.count <= 0 {
+            return scheduler.hold_current();
+        }
+        if (self.v1 & 1) == 0 {
+            self.v1 >>= 1;
+            scheduler.release(ID_DEVICE_A)
+        } else {
+            self.v1 = (self.v1 >> 1) ^ 0xD008;
+            scheduler.release(ID_DEVICE_B)
+        }
+    }
+}
+
+struct DeviceTask {
+    v1: Option<Box<Packet>>,
+}
+
+impl Task for DeviceTask {
+    fn run(&mut self, scheduler: &mut Scheduler, packet: Option<Box<Packet>>) -> Option<usize> {
+        match packet {
+            None => match self.v1.take() {
+                None => scheduler.suspend_current(),
+                Some(v) => scheduler.queue(v),
+            },
+            Some(packet) => {
+                self.v1 = Some(packet);
+                scheduler.hold_current()
+            }
+        }
+    }
+
+    fn for_each_packet(&self, f: &mut dyn FnMut(&Packet)) {
+        for_each_in_queue(&self.v1, f);
+    }
+}
+
+struct WorkerTask {
+    v1: usize,
+    v2: i32,
+}
+
+impl Task for WorkerTask {
+    fn run(&mut self, scheduler: &mut Scheduler, packet: Option<Box<Packet>>) -> Option<usize> {
+        let Some(mut packet) = packet else {
+            return scheduler.suspend_current();
+        };
+
+        self.v1 = if self.v1 == ID_HANDLER_A {
+            ID_HANDLER_B
+        } else {
+            ID_HANDLER_A
+        };
+        packet.id = self.v1;
+        packet.a1 = 0;
+        for i in 0..DATA_SIZE {
+            self.v2 += 1;
+            if self.v2 > 26 {
+                self.v2 = 1;
+            }
+            packet.a2[i] = unsafe { taint_i32(self.v2) };
+        }
+        packet.tainted_payload = true;
+        scheduler.queue(packet)
+    }
+}
+
+struct HandlerTask {
+    v1: Option<Box<Packet>>,
+    v2: Option<Box<Packet>>,
+}
+
+impl Task for HandlerTask {
+    fn run(&mut self, scheduler: &mut Scheduler, packet: Option<Box<Packet>>) -> Option<usize> {
+        if let Some(packet) = packet {
+            if packet.kind == KIND_WORK {
+                self.v1 = packet.add_to(self.v1.take());
+            } else {
+                self.v2 = packet.add_to(self.v2.take());
+            }
+        }
+
+        if let Some(work) = self.v1.as_mut() {
+            let count = work.a1 as usize;
+            if count < DATA_SIZE {
+                if let Some(mut v) = self.v2.take() {
+                    self.v2 = v.link.take();
+                    v.a1 = work.a2[count];
+                    v.tainted_payload = work.tainted_payload;
+                    work.a1 = count as i32 + 1;
+                    return scheduler.queue(v);
+                }
+            } else {
+                let mut v = self.v1.take().unwrap();
+                self.v1 = v.link.take();
+                return scheduler.queue(v);
+            }
+        }
+
+        scheduler.suspend_current()
+    }
+
+    fn for_each_packet(&self, f: &mut dyn FnMut(&Packet)) {
+        for_each_in_queue(&self.v1, f);
+        for_each_in_queue(&self.v2, f);
+    }
+}
+
+fn for_each_in_queue(queue: &Option<Box<Packet>>, f: &mut dyn FnMut(&Packet)) {
+    let mut current = queue.as_deref();
+    while let Some(packet) = current {
+        f(packet);
+        current = packet.link.as_deref();
+    }
+}
+
+struct TaskControlBlock {
+    link: Option<usize>,
+    priority: i32,
+    queue: Option<Box<Packet>>,
+    task: Option<Box<dyn Task>>,
+    state: i32,
+}
+
+impl TaskControlBlock {
+    fn mark_as_not_held(&mut self) {
+        self.state &= STATE_NOT_HELD;
+    }
+
+    fn mark_as_held(&mut self) {
+        self.state |= STATE_HELD;
+    }
+
+    fn mark_as_suspended(&mut self) {
+        self.state |= STATE_SUSPENDED;
+    }
+
+    fn mark_as_runnable(&mut self) {
+        self.state |= STATE_RUNNABLE;
+    }
+
+    fn is_held_or_suspended(&self) -> bool {
+        (self.state & STATE_HELD) != 0 || self.state == STATE_SUSPENDED
+    }
+
+    /// Dequeues the packet the task should run with, if it is runnable.
+    fn take_packet(&mut self) -> Option<Box<Packet>> {
+        if self.state != STATE_SUSPENDED_RUNNABLE {
+            return None;
+        }
+        let mut packet = self.queue.take().unwrap();
+        self.queue = packet.link.take();
+        self.state = if self.queue.is_none() {
+            STATE_RUNNING
+        } else {
+            STATE_RUNNABLE
+        };
+        Some(packet)
+    }
+}
+
+struct Scheduler {
+    queue_count: i32,
+    hold_count: i32,
+    blocks: Vec<TaskControlBlock>,
+    list: Option<usize>,
+    current_id: usize,
+}
+
+impl Scheduler {
+    fn new() -> Self {
+        Scheduler {
+            queue_count: 0,
+            hold_count: 0,
+            blocks: Vec::new(),
+            list: None,
+            current_id: 0,
+        }
+    }
+
+    fn add_task(
+        &mut self,
+        id: usize,
+        priority: i32,
+        queue: Option<Box<Packet>>,
+        task: Box<dyn Task>,
+    ) {
+        assert_eq!(id, self.blocks.len());
+        let state = if queue.is_none() {
+            STATE_SUSPENDED
+        } else {
+            STATE_SUSPENDED_RUNNABLE
+        };
+        self.blocks.push(TaskControlBlock {
+            link: self.list,
+            priority,
+            queue,
+            task: Some(task),
+            state,
+        });
+        self.list = Some(id);
+    }
+
+    fn add_running_task(
+        &mut self,
+        id: usize,
+        priority: i32,
+        queue: Option<Box<Packet>>,
+        task: Box<dyn Task>,
+    ) {
+        self.add_task(id, priority, queue, task);
+        self.blocks[id].state = STATE_RUNNING;
+    }
+
+    fn schedule(&mut self) {
+        let mut current = self.list;
+        while let Some(id) = current {
+            if self.blocks[id].is_held_or_suspended() {
+                current = self.blocks[id].link;
+                continue;
+            }
+
+            self.current_id = id;
+            let packet = self.blocks[id].take_packet();
+            let mut task = self.blocks[id].task.take().unwrap();
+            current = task.run(self, packet);
+            self.blocks[id].task = Some(task);
+        }
+    }
+
+    fn release(&mut self, id: usize) -> Option<usize> {
+        self.blocks[id].mark_as_not_held();
+        if self.blocks[id].priority > self.blocks[self.current_id].priority {
+            Some(id)
+        } else {
+            Some(self.current_id)
+        }
+    }
+
+    fn hold_current(&mut self) -> Option<usize> {
+        self.hold_count += 1;
+        let current = &mut self.blocks[self.current_id];
+        current.mark_as_held();
+        current.link
+    }
+
+    fn suspend_current(&mut self) -> Option<usize> {
+        self.blocks[self.current_id].mark_as_suspended();
+        Some(self.current_id)
+    }
+
+    /// Moves `packet` to the queue of the task it is addressed to.
+    fn queue(&mut self, mut packet: Box<Packet>) -> Option<usize> {
+        let target = packet.id;
+        self.queue_count += 1;
+        packet.link = None;
+        packet.id = self.current_id;
+
+        let current_priority = self.blocks[self.current_id].priority;
+        let tcb = &mut self.blocks[target];
+        if tcb.queue.is_none() {
+            tcb.queue = Some(packet);
+            tcb.mark_as_runnable();
+            if tcb.priority > current_priority {
+                return Some(target);
+            }
+        } else {
+            tcb.queue = packet.add_to(tcb.queue.take());
+        }
+        Some(self.current_id)
+    }
+
+    fn for_each_packet(&self, f: &mut dyn FnMut(&Packet)) {
+        for tcb in &self.blocks {
+            for_each_in_queue(&tcb.queue, f);
+            tcb.task.as_ref().unwrap().for_each_packet(f);
+        }
+    }
+}
+
+fn setup_scheduler(count: i32) -> Scheduler {
+    let mut scheduler = Scheduler::new();
+    scheduler.add_running_task(ID_IDLE, 0, None, Box::new(IdleTask { v1: 1, count }));
+
+    let mut queue = Packet::new(None, ID_WORKER, KIND_WORK);
+    queue = Packet::new(Some(queue), ID_WORKER, KIND_WORK);
+    let worker = WorkerTask {
+        v1: ID_HANDLER_A,
+        v2: 0,
+    };
+    scheduler.add_task(ID_WORKER, 1000, Some(queue), Box::new(worker));
+
+    for (handler_id, device_id, priority) in [
+        (ID_HANDLER_A, ID_DEVICE_A, 2000),
+        (ID_HANDLER_B, ID_DEVICE_B, 3000),
+    ] {
+        let mut queue = Packet::new(None, device_id, KIND_DEVICE);
+        queue = Packet::new(Some(queue), device_id, KIND_DEVICE);
+        queue = Packet::new(Some(queue), device_id, KIND_DEVICE);
+        let handler = HandlerTask { v1: None, v2: None };
+        scheduler.add_task(handler_id, priority, Some(queue), Box::new(handler));
+    }
+
+    scheduler.add_task(ID_DEVICE_A, 4000, None, Box::new(DeviceTask { v1: None }));
+    scheduler.add_task(ID_DEVICE_B, 5000, None, Box::new(DeviceTask { v1: None }));
+
+    scheduler
+}
+
+/// Checks the taint of every packet still queued or held by a task.
+fn check_packets(scheduler: &Scheduler) {
+    let mut packet_count = 0;
+    scheduler.for_each_packet(&mut |packet| {
+        packet_count += 1;
+        unsafe { assert_is_not_tainted_i32(packet.id as i32) };
+
+        if packet.kind == KIND_WORK {
+            // The handler's read index into the payload.
+            unsafe { assert_is_not_tainted_i32(packet.a1) };
+            for &data in &packet.a2 {
+                if packet.tainted_payload {
+                    unsafe { assert_is_tainted_i32(data) };
+                } else {
+                    unsafe { assert_is_not_tainted_i32(data) };
+                }
+            }
+        } else if packet.tainted_payload {
+            unsafe { assert_is_tainted_i32(packet.a1) };
+        } else {
+            unsafe { assert_is_not_tainted_i32(packet.a1) };
+        }
+    });
+    assert_eq!(packet_count, PACKET_COUNT);
+}
+
+fn get_expected_counts(count: i32) -> Option<(i32, i32)> {
+    match count {
+        1000 => Some((2322, 928)),
+        10000 => Some((23246, 9297)),
+        _ => None,
+    }
+}
+
+fn benchmark(n: i32) -> i32 {
+    let mut scheduler = setup_scheduler(n);
+    scheduler.schedule();
+
+    if let Some(expected) = get_expected_counts(n) {
+        assert_eq!((scheduler.queue_count, scheduler.hold_count), expected);
+    }
+    unsafe { assert_is_not_tainted_i32(scheduler.queue_count) };
+    unsafe { assert_is_not_tainted_i32(scheduler.hold_count) };
+    check_packets(&scheduler);
+
+    scheduler.queue_count
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n)
+}
@@ -0,0 +1,270 @@
+// Sequence alignment benchmark
+// Fills the dynamic programming tables of Levenshtein distance and of a
+// weighted global alignment in the manner of Needleman-Wunsch, both taking the
+// `min` of three neighbours, for two DNA sequences generated as in fasta. The
+// middle third of the first sequence is tainted.
+//
+// A substitution candidate is tainted if its base of the first sequence or its
+// diagonal neighbour is, the gap candidates if their neighbour is. `min` selects
+// a candidate, which yields the taint of the chosen one, so cells below the
+// tainted rows stay clean where the cheapest alignment deletes those bases. The
+// expected taint of every cell follows the chosen candidates; where candidates
+// of different taint tie, either may be chosen and the cell is not checked.
+
+use std::ops::Range;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+// Substituting a purine for a pyrimidine or the reverse costs twice as much as
+// substituting within a class.
+const TRANSITION_COST: i32 = 1;
+const TRANSVERSION_COST: i32 = 2;
+const GAP_COST: i32 = 2;
+
+struct AminoAcid {
+    c: u8,
+    p: f64,
+}
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+fn homosapiens() -> Vec<AminoAcid> {
+    let mut homosapiens = vec![
+        AminoAcid {
+            c: b'a',
+            p: 0.302954942668,
+        },
+        AminoAcid {
+            c: b'c',
+            p: 0.1979883004921,
+        },
+        AminoAcid {
+            c: b'g',
+            p: 0.1975473066391,
+        },
+        AminoAcid {
+            c: b't',
+            p: 0.3015094502008,
+        },
+    ];
+
+    let mut cp = 0.0;
+    for acid in homosapiens.iter_mut() {
+        cp += acid.p;
+        acid.p = cp;
+    }
+    homosapiens
+}
+
+fn random_sequence(rng: &mut Random, acids: &[AminoAcid], n: usize) -> Vec<u8> {
+    (0..n)
+        .map(|_| {
+            let r = rng.gen_random(1.0);
+            acids
+                .iter()
+                .find(|acid| r < acid.p)
+                .unwrap_or(&acids[acids.len() - 1])
+                .c
+        })
+        .collect()
+}
+
+/// Row-major `(rows + 1) x (cols + 1)` dynamic programming table.
+struct Table {
+    cells: Vec<i32>,
+    cols: usize,
+}
+
+impl Table {
+    fn new(rows: usize, cols: usize) -> Self {
+        Table {
+            cells: vec![0; (rows + 1) * (cols + 1)],
+            cols: cols + 1,
+        }
+    }
+
+    fn get(&self, i: usize, j: usize) -> i32 {
+        self.cells[i * self.cols + j]
+    }
+
+    fn set(&mut self, i: usize, j: usize, val: i32) {
+        self.cells[i * self.cols + j] = val;
+    }
+}
+
+/// Edit costs of an alignment.
+struct Costs {
+    substitution: fn(u8, u8) -> i32,
+    gap: i32,
+}
+
+const LEVENSHTEIN: Costs = Costs {
+    substitution: |x, y| (x != y) as i32,
+    gap: 1,
+};
+
+const NEEDLEMAN_WUNSCH: Costs = Costs {
+    substitution: substitution_cost,
+    gap: GAP_COST,
+};
+
+fn is_purine(c: u8) -> i32 {
+    ((c == b'a') | (c == b'g')) as i32
+}
+
+/// Computed without multiplying by `x != y`, which could be turned into a
+/// select of the constant 0 for equal bases.
+fn substitution_cost(x: u8, y: u8) -> i32 {
+    let transversion = is_purine(x) ^ is_purine(y);
+    (x != y) as i32 * TRANSITION_COST + transversion * (TRANSVERSION_COST - TRANSITION_COST)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Taint {
+    Clean,
+    Tainted,
+    // Candidates of different taint tied.
+    Unknown,
+}
+
+impl Taint {
+    fn or(self, tainted: bool) -> Taint {
+        if tainted { Taint::Tainted } else { self }
+    }
+}
+
+impl Costs {
+    /// Returns the three candidates of cell `(i, j)`: substitution, deletion
+    /// and insertion.
+    fn candidates(&self, table: &Table, a: &[u8], b: &[u8], i: usize, j: usize) -> [i32; 3] {
+        [
+            table.get(i - 1, j - 1) + (self.substitution)(a[i - 1], b[j - 1]),
+            table.get(i - 1, j) + self.gap,
+            table.get(i, j - 1) + self.gap,
+        ]
+    }
+
+    /// Fills the table of minimal global alignment costs.
+    fn align(&self, a: &[u8], b: &[u8]) -> Table {
+        let mut table = Table::new(a.len(), b.len());
+        for i in 0..=a.len() {
+            table.set(i, 0, i as i32 * self.gap);
+        }
+        for j in 0..=b.len() {
+            table.set(0, j, j as i32 * self.gap);
+        }
+
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                let [substitution, deletion, insertion] = self.candidates(&table, a, b, i, j);
+                table.set(i, j, substitution.min(deletion).min(insertion));
+            }
+        }
+        table
+    }
+
+    /// Returns the expected taint of every cell of `table`, given the rows of
+    /// `a` in `tainted`.
+    fn expected_taint(
+        &self,
+        table: &Table,
+        a: &[u8],
+        b: &[u8],
+        tainted: &Range<usize>,
+    ) -> Vec<Taint> {
+        let mut expected = vec![Taint::Clean; table.cells.len()];
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                let candidates = self.candidates(table, a, b, i, j);
+                let taints = [
+                    expected[(i - 1) * table.cols + j - 1].or(tainted.contains(&(i - 1))),
+                    expected[(i - 1) * table.cols + j],
+                    expected[i * table.cols + j - 1],
+                ];
+                let cell = table.get(i, j);
+                let mut chosen = (0..3).filter(|&k| candidates[k] == cell).map(|k| taints[k]);
+                let first = chosen.next().unwrap();
+                expected[i * table.cols + j] = if chosen.all(|taint| taint == first) {
+                    first
+                } else {
+                    Taint::Unknown
+                };
+            }
+        }
+        expected
+    }
+}
+
+/// Asserts the taint of every cell whose taint is known, returning how many
+/// are tainted.
+fn check_table(table: &Table, expected: &[Taint]) -> usize {
+    let mut tainted = 0;
+    for (&cell, &taint) in table.cells.iter().zip(expected) {
+        match taint {
+            Taint::Clean => unsafe { assert_is_not_tainted_i32(cell) },
+            Taint::Tainted => {
+                unsafe { assert_is_tainted_i32(cell) };
+                tainted += 1;
+            }
+            Taint::Unknown => {}
+        }
+    }
+    tainted
+}
+
+fn benchmark(n: usize) -> i32 {
+    let acids = homosapiens();
+    let mut rng = Random::new();
+    let tainted = n / 3..2 * n / 3;
+    let a: Vec<u8> = random_sequence(&mut rng, &acids, n)
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if tainted.contains(&i) {
+                unsafe { taint_i32(c as i32) as u8 }
+            } else {
+                c
+            }
+        })
+        .collect();
+    let b = random_sequence(&mut rng, &acids, n);
+
+    let distances = LEVENSHTEIN.align(&a, &b);
+    let expected = LEVENSHTEIN.expected_taint(&distances, &a, &b, &tainted);
+    check_table(&distances, &expected);
+    assert!(distances.get(n, n) <= n as i32);
+
+    let costs = NEEDLEMAN_WUNSCH.align(&a, &b);
+    let expected = NEEDLEMAN_WUNSCH.expected_taint(&costs, &a, &b, &tainted);
+    let tainted_cells = check_table(&costs, &expected);
+    assert!(costs.get(n, n) <= n as i32 * TRANSVERSION_COST);
+
+    tainted_cells as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
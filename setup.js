@@ -44,6 +44,131 @@ export const benchmarks = [
     //     iterations: 2,
     //     input: 0, // doesn't matter
     // },
+    // {
+    //     name: "lz77",
+    //     iterations: 2,
+    //     input: 100000,
+    // },
+    // {
+    //     name: "bytecode-vm",
+    //     iterations: 2,
+    //     input: 100000,
+    // },
+    // {
+    //     name: "richards",
+    //     iterations: 2,
+    //     input: 10000,
+    // },
+    // {
+    //     name: "json",
+    //     iterations: 2,
+    //     input: 1000,
+    // },
+    // {
+    //     name: "havlak",
+    //     iterations: 2,
+    //     input: 2,
+    // },
+    // {
+    //     name: "sorting",
+    //     iterations: 2,
+    //     input: 100000,
+    // },
+    // {
+    //     name: "dijkstra",
+    //     iterations: 2,
+    //     input: 10000,
+    // },
+    // {
+    //     name: "ray-tracer",
+    //     iterations: 2,
+    //     input: 256,
+    // },
+    // {
+    //     name: "huffman",
+    //     iterations: 2,
+    //     input: 10000,
+    // },
+    // {
+    //     name: "checksum",
+    //     iterations: 2,
+    //     input: 1000,
+    // },
+    // {
+    //     name: "game-of-life",
+    //     iterations: 2,
+    //     input: 100,
+    // },
+    // {
+    //     name: "sequence-alignment",
+    //     iterations: 2,
+    //     input: 1000,
+    // },
+    // {
+    //     name: "convolution",
+    //     iterations: 2,
+    //     input: 512,
+    // },
+    // {
+    //     name: "fft",
+    //     iterations: 2,
+    //     input: 65536,
+    // },
+    // {
+    //     name: "codec",
+    //     iterations: 2,
+    //     input: 100000,
+    // },
+    // {
+    //     name: "deltablue",
+    //     iterations: 2,
+    //     input: 10000,
+    // },
+    // {
+    //     name: "async-executor",
+    //     iterations: 2,
+    //     input: 100000,
+    // },
+    // {
+    //     name: "bloom-filter",
+    //     iterations: 2,
+    //     input: 100000,
+    // },
+    // {
+    //     name: "prng",
+    //     iterations: 2,
+    //     input: 1000000,
+    // },
+    // {
+    //     name: "formatting",
+    //     iterations: 2,
+    //     input: 100000,
+    // },
+    // {
+    //     name: "float-conversion",
+    //     iterations: 2,
+    //     input: 100000,
+    // },
+    // {
+    //     name: "matrix",
+    //     iterations: 2,
+    //     input: 200,
+    // },
+    // {
+    //     name: "url-html",
+    //     iterations: 2,
+    //     input: 10000,
+    // },
+    // {
+    //     name: "linked-list",
+    //     iterations: 2,
+    //     input: 1000000,
+    // },
+    // {
+    //     name: "expression",
+    //     iterations: 2,
+    //     input: 10000,
+    // },
 ];
 
 export const benchmarkTypes = {
@@ -0,0 +1,225 @@
+// Sorting benchmark
+// Sorts records with tainted keys and untainted payloads three ways: quicksort
+// (keys only steer comparisons), mergesort (records are copied through a
+// buffer) and LSD radix sort (key digits are used as bucket indices).
+//
+// The payloads stay clean, although their final order is decided by the keys.
+// The swap and merge counts and the position of each payload only depend on
+// the keys through comparisons, and radix sort only uses key digits as indices
+// of the bucket counts it increments, so none of these is tainted.
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const RADIX_BITS: u32 = 8;
+const RADIX: usize = 1 << RADIX_BITS;
+const KEY_BITS: u32 = 20;
+
+#[derive(Clone, Copy)]
+struct Record {
+    key: i32,
+    payload: i32,
+}
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+}
+
+fn setup_records(n: usize) -> Vec<Record> {
+    let mut rng = Random::new();
+    (0..n)
+        .map(|i| Record {
+            key: unsafe { taint_i32(rng.gen_random((1 << KEY_BITS) as f64) as i32) },
+            payload: i as i32,
+        })
+        .collect()
+}
+
+/// Asserts that the position each payload was sorted to is not tainted.
+fn check_positions(sorted: &[Record]) {
+    let mut positions = vec![0; sorted.len()];
+    for (i, record) in sorted.iter().enumerate() {
+        positions[record.payload as usize] = i as i32;
+    }
+    for position in positions {
+        unsafe { assert_is_not_tainted_i32(position) };
+    }
+}
+
+/// Hoare-partition quicksort, returning the number of swaps performed.
+fn quicksort(records: &mut [Record]) -> i32 {
+    if records.len() <= 1 {
+        return 0;
+    }
+
+    let pivot = records[(records.len() - 1) / 2].key;
+    let mut i = 0;
+    let mut j = records.len() - 1;
+    let mut swaps = 0;
+    loop {
+        while records[i].key < pivot {
+            i += 1;
+        }
+        while records[j].key > pivot {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        records.swap(i, j);
+        swaps += 1;
+        i += 1;
+        j -= 1;
+    }
+
+    let (left, right) = records.split_at_mut(j + 1);
+    swaps + quicksort(left) + quicksort(right)
+}
+
+/// Top-down mergesort, returning how many records were taken from the left
+/// half while merging.
+fn mergesort(records: &mut [Record], buffer: &mut [Record]) -> i32 {
+    let len = records.len();
+    if len <= 1 {
+        return 0;
+    }
+
+    let mid = len / 2;
+    let mut taken_left = mergesort(&mut records[..mid], &mut buffer[..mid]);
+    taken_left += mergesort(&mut records[mid..], &mut buffer[mid..]);
+
+    let (mut i, mut j) = (0, mid);
+    for slot in buffer[..len].iter_mut() {
+        if j == len || (i < mid && records[i].key <= records[j].key) {
+            *slot = records[i];
+            i += 1;
+            taken_left += 1;
+        } else {
+            *slot = records[j];
+            j += 1;
+        }
+    }
+    records.copy_from_slice(&buffer[..len]);
+
+    taken_left
+}
+
+/// LSD radix sort on `KEY_BITS`-bit keys, returning the bucket counts of the
+/// last pass.
+fn radix_sort(records: &mut Vec<Record>) -> Vec<i32> {
+    let mut buffer = vec![Record { key: 0, payload: 0 }; records.len()];
+    let mut counts = vec![0; RADIX];
+
+    let mut shift = 0;
+    while shift < KEY_BITS {
+        counts.iter_mut().for_each(|count| *count = 0);
+        for record in records.iter() {
+            counts[((record.key >> shift) as usize) & (RADIX - 1)] += 1;
+        }
+
+        let mut offsets = vec![0; RADIX];
+        for digit in 1..RADIX {
+            offsets[digit] = offsets[digit - 1] + counts[digit - 1];
+        }
+
+        for record in records.iter() {
+            let digit = ((record.key >> shift) as usize) & (RADIX - 1);
+            buffer[offsets[digit] as usize] = *record;
+            offsets[digit] += 1;
+        }
+
+        std::mem::swap(records, &mut buffer);
+        shift += RADIX_BITS;
+    }
+
+    counts
+}
+
+/// Checks that `sorted` is ordered by key and, for stable sorts, that equal keys
+/// kept their payloads in input order. Payloads are the input indices, so this
+/// also checks that `sorted` is a permutation of the input.
+fn check_sorted(sorted: &[Record], input: &[Record], stable: bool) {
+    let mut seen = vec![false; input.len()];
+    for (i, record) in sorted.iter().enumerate() {
+        let original = input[record.payload as usize];
+        assert_eq!(original.key, record.key);
+        assert!(!seen[record.payload as usize]);
+        seen[record.payload as usize] = true;
+
+        if i > 0 {
+            let prev = sorted[i - 1];
+            assert!(prev.key <= record.key);
+            if stable && prev.key == record.key {
+                assert!(prev.payload < record.payload);
+            }
+        }
+    }
+}
+
+/// Explicit flows: keys and key comparisons are tainted, payloads are not.
+fn check_explicit(sorted: &[Record]) {
+    for (i, record) in sorted.iter().enumerate() {
+        unsafe { assert_is_tainted_i32(record.key) };
+        unsafe { assert_is_not_tainted_i32(record.payload) };
+        if i > 0 {
+            let ordered = (sorted[i - 1].key <= record.key) as i32;
+            unsafe { assert_is_tainted_i32(ordered) };
+        }
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    let input = setup_records(n);
+
+    let mut records = input.clone();
+    let swaps = quicksort(&mut records);
+    check_sorted(&records, &input, false);
+    check_explicit(&records);
+    // Swaps are only counted when key comparisons say so.
+    unsafe { assert_is_not_tainted_i32(swaps) };
+    check_positions(&records);
+
+    let mut records = input.clone();
+    let mut buffer = records.clone();
+    let taken_left = mergesort(&mut records, &mut buffer);
+    check_sorted(&records, &input, true);
+    check_explicit(&records);
+    unsafe { assert_is_not_tainted_i32(taken_left) };
+    check_positions(&records);
+
+    let mut records = input.clone();
+    let counts = radix_sort(&mut records);
+    check_sorted(&records, &input, true);
+    check_explicit(&records);
+    check_positions(&records);
+    assert_eq!(counts.iter().sum::<i32>(), n as i32);
+    for count in counts {
+        unsafe { assert_is_not_tainted_i32(count) };
+    }
+
+    swaps
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}
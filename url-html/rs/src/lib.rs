@@ -0,0 +1,361 @@
+// URL and HTML benchmark
+// Parses URLs into scheme, host, port, path, query parameters and fragment,
+// percent-decodes the parameter values and the fragment, and renders each URL
+// as an HTML list item, once inserting the components as they are and once
+// through an escaping function that sanitizes every byte it copies.
+//
+// Query values and fragments are user input and tainted, as are whole URLs
+// once every `TAINTED_PERIOD`. Percent-decoding computes bytes from tainted hex
+// digits, so decoded components keep their taint. In the raw rendering, the
+// spans of tainted components are tainted and the markup is not, while no byte
+// of the escaped rendering is tainted, entities being constants.
+
+use std::ops::Range;
+
+#[link(wasm_import_module = "taint")]
+unsafe extern "C" {
+    fn taint_i32(val: i32) -> i32;
+    fn sanitize_i32(val: i32) -> i32;
+    fn assert_is_tainted_i32(val: i32);
+    fn assert_is_not_tainted_i32(val: i32);
+}
+
+const TAINTED_PERIOD: usize = 4;
+const SCHEMES: [&str; 2] = ["http", "https"];
+const HOSTS: [&str; 3] = ["example.com", "benchmark.test", "taint.example.org"];
+const PATHS: [&str; 4] = ["/", "/index.html", "/a/b/c", "/search"];
+const KEYS: [&str; 4] = ["q", "id", "lang", "redirect"];
+const VALUES: [&str; 6] = [
+    "hello",
+    "%3Cscript%3Ealert(1)%3C%2Fscript%3E",
+    "caf%C3%A9",
+    "a%26b%3Dc",
+    "%22quoted%22",
+    "it%27s",
+];
+const FRAGMENTS: [&str; 2] = ["top", "%3Cimg%20src%3Dx%3E"];
+
+struct Random {
+    last: u32,
+}
+
+impl Random {
+    fn new() -> Self {
+        Self { last: 42 }
+    }
+
+    fn gen_random(&mut self, max: f64) -> f64 {
+        const IM: u32 = 139968;
+        const IA: u32 = 3877;
+        const IC: u32 = 29573;
+
+        self.last = (self.last * IA + IC) % IM;
+        max * (self.last as f64) / (IM as f64)
+    }
+
+    fn choose<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.gen_random(items.len() as f64) as usize]
+    }
+}
+
+fn push_text(url: &mut Vec<u8>, text: &str, tainted: bool) {
+    for &byte in text.as_bytes() {
+        url.push(if tainted {
+            unsafe { taint_i32(byte as i32) as u8 }
+        } else {
+            byte
+        });
+    }
+}
+
+/// Generates a URL, tainting its query values and fragment, or all of it if
+/// `tainted`.
+fn generate_url(rng: &mut Random, tainted: bool) -> Vec<u8> {
+    let mut url = Vec::new();
+    push_text(&mut url, rng.choose(&SCHEMES), tainted);
+    push_text(&mut url, "://", tainted);
+    push_text(&mut url, rng.choose(&HOSTS), tainted);
+    if rng.gen_random(1.0) < 0.5 {
+        let port = 1 + rng.gen_random(65535.0) as u16;
+        push_text(&mut url, &format!(":{}", port), tainted);
+    }
+    push_text(&mut url, rng.choose(&PATHS), tainted);
+    let parameters = rng.gen_random(4.0) as usize;
+    for i in 0..parameters {
+        push_text(&mut url, if i == 0 { "?" } else { "&" }, tainted);
+        push_text(&mut url, rng.choose(&KEYS), tainted);
+        push_text(&mut url, "=", tainted);
+        push_text(&mut url, rng.choose(&VALUES), true);
+    }
+    if rng.gen_random(1.0) < 0.5 {
+        push_text(&mut url, "#", tainted);
+        push_text(&mut url, rng.choose(&FRAGMENTS), true);
+    }
+    url
+}
+
+struct Url<'a> {
+    scheme: &'a [u8],
+    host: &'a [u8],
+    port: Option<u16>,
+    path: &'a [u8],
+    query: Vec<(&'a [u8], &'a [u8])>,
+    fragment: Option<&'a [u8]>,
+}
+
+/// Splits `s` at the first byte matching `delimiter`, which stays in the
+/// second part.
+fn split_at_any(s: &[u8], delimiter: impl Fn(u8) -> bool) -> (&[u8], &[u8]) {
+    let end = s.iter().position(|&c| delimiter(c)).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+fn parse_port(digits: &[u8]) -> Option<u16> {
+    if digits.is_empty() {
+        return None;
+    }
+    let mut port: u32 = 0;
+    for &c in digits {
+        if !c.is_ascii_digit() {
+            return None;
+        }
+        port = port * 10 + (c - b'0') as u32;
+        if port > u16::MAX as u32 {
+            return None;
+        }
+    }
+    Some(port as u16)
+}
+
+fn parse_url(url: &[u8]) -> Option<Url<'_>> {
+    let separator = url.windows(3).position(|w| w == b"://")?;
+    let scheme = &url[..separator];
+    if scheme.is_empty() || !scheme.iter().all(u8::is_ascii_alphabetic) {
+        return None;
+    }
+
+    let rest = &url[separator + 3..];
+    let (authority, rest) = split_at_any(rest, |c| matches!(c, b'/' | b'?' | b'#'));
+    let (host, port) = match authority.iter().position(|&c| c == b':') {
+        Some(colon) => (
+            &authority[..colon],
+            Some(parse_port(&authority[colon + 1..])?),
+        ),
+        None => (authority, None),
+    };
+    if host.is_empty() {
+        return None;
+    }
+
+    let (path, rest) = split_at_any(rest, |c| matches!(c, b'?' | b'#'));
+    let (query, fragment) = split_at_any(rest, |c| c == b'#');
+    let query = match query.split_first() {
+        Some((_, query)) => query
+            .split(|&c| c == b'&')
+            .map(|parameter| {
+                let (key, value) = split_at_any(parameter, |c| c == b'=');
+                (key, value.get(1..).unwrap_or(&[]))
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    Some(Url {
+        scheme,
+        host,
+        port,
+        path: if path.is_empty() { b"/" } else { path },
+        query,
+        fragment: fragment.get(1..),
+    })
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn percent_decode(s: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] == b'%'
+            && i + 2 < s.len()
+            && let (Some(high), Some(low)) = (hex_value(s[i + 1]), hex_value(s[i + 2]))
+        {
+            decoded.push((high << 4) | low);
+            i += 3;
+        } else {
+            decoded.push(s[i]);
+            i += 1;
+        }
+    }
+    decoded
+}
+
+/// Escapes HTML special characters, sanitizing the bytes copied as they are.
+fn escape_html(text: &[u8], out: &mut Vec<u8>) {
+    for &byte in text {
+        match byte {
+            b'<' => out.extend_from_slice(b"&lt;"),
+            b'>' => out.extend_from_slice(b"&gt;"),
+            b'&' => out.extend_from_slice(b"&amp;"),
+            b'"' => out.extend_from_slice(b"&quot;"),
+            b'\'' => out.extend_from_slice(b"&#39;"),
+            _ => out.push(unsafe { sanitize_i32(byte as i32) as u8 }),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Part {
+    Scheme,
+    Host,
+    Port,
+    Path,
+    Key,
+    Value,
+    Fragment,
+}
+
+struct Html {
+    bytes: Vec<u8>,
+    // The span of each component, for the checks.
+    spans: Vec<(Range<usize>, Part)>,
+    escape: bool,
+}
+
+impl Html {
+    fn new(escape: bool) -> Self {
+        Html {
+            bytes: Vec::new(),
+            spans: Vec::new(),
+            escape,
+        }
+    }
+
+    fn markup(&mut self, markup: &str) {
+        self.bytes.extend_from_slice(markup.as_bytes());
+    }
+
+    fn text(&mut self, part: Part, text: &[u8]) {
+        let start = self.bytes.len();
+        if self.escape {
+            escape_html(text, &mut self.bytes);
+        } else {
+            self.bytes.extend_from_slice(text);
+        }
+        self.spans.push((start..self.bytes.len(), part));
+    }
+
+    fn render(&mut self, url: &Url) {
+        self.markup("<li><a href=\"");
+        self.text(Part::Scheme, url.scheme);
+        self.markup("://");
+        self.text(Part::Host, url.host);
+        self.text(Part::Path, url.path);
+        self.markup("\">");
+        self.text(Part::Host, url.host);
+        if let Some(port) = url.port {
+            self.markup(":");
+            let mut digits = Vec::new();
+            let mut port = port;
+            loop {
+                digits.push(b'0' + (port % 10) as u8);
+                port /= 10;
+                if port == 0 {
+                    break;
+                }
+            }
+            digits.reverse();
+            self.text(Part::Port, &digits);
+        }
+        self.markup("</a><dl>");
+        for &(key, value) in &url.query {
+            self.markup("<dt>");
+            self.text(Part::Key, key);
+            self.markup("</dt><dd>");
+            self.text(Part::Value, &percent_decode(value));
+            self.markup("</dd>");
+        }
+        self.markup("</dl>");
+        if let Some(fragment) = url.fragment {
+            self.markup("<p>");
+            self.text(Part::Fragment, &percent_decode(fragment));
+            self.markup("</p>");
+        }
+        self.markup("</li>\n");
+    }
+
+    /// Asserts that the spans of tainted components are tainted, and nothing
+    /// else, returning the number of tainted bytes.
+    fn check(&self, url_tainted: bool) -> usize {
+        let mut tainted = vec![false; self.bytes.len()];
+        for (range, part) in &self.spans {
+            let part_tainted = match part {
+                Part::Value | Part::Fragment => true,
+                _ => url_tainted,
+            };
+            if part_tainted && !self.escape {
+                tainted[range.clone()].fill(true);
+            }
+            if self.escape {
+                assert!(
+                    !self.bytes[range.clone()]
+                        .iter()
+                        .any(|c| matches!(c, b'<' | b'>' | b'"' | b'\''))
+                );
+            }
+        }
+
+        for (&byte, &byte_tainted) in self.bytes.iter().zip(&tainted) {
+            if byte_tainted {
+                unsafe { assert_is_tainted_i32(byte as i32) };
+            } else {
+                unsafe { assert_is_not_tainted_i32(byte as i32) };
+            }
+        }
+        tainted.iter().filter(|&&t| t).count()
+    }
+}
+
+fn benchmark(n: usize) -> i32 {
+    let url = parse_url(b"https://example.com:8080/a/b?q=%3Cb%3E&id=1#top").unwrap();
+    assert_eq!(url.port, Some(8080));
+    assert_eq!(url.query.len(), 2);
+    assert_eq!(percent_decode(url.query[0].1), b"<b>");
+    let mut html = Html::new(true);
+    html.render(&url);
+    assert_eq!(
+        html.bytes,
+        b"<li><a href=\"https://example.com/a/b\">example.com:8080</a><dl>\
+          <dt>q</dt><dd>&lt;b&gt;</dd><dt>id</dt><dd>1</dd></dl><p>top</p></li>\n"
+    );
+
+    let mut rng = Random::new();
+    let mut tainted_bytes = 0;
+    for i in 0..n {
+        let url_tainted = i.is_multiple_of(TAINTED_PERIOD);
+        let bytes = generate_url(&mut rng, url_tainted);
+        let url = parse_url(&bytes).unwrap();
+
+        let mut raw = Html::new(false);
+        raw.render(&url);
+        tainted_bytes += raw.check(url_tainted);
+
+        let mut escaped = Html::new(true);
+        escaped.render(&url);
+        assert_eq!(escaped.check(url_tainted), 0);
+    }
+
+    tainted_bytes as i32
+}
+
+#[unsafe(no_mangle)]
+fn main(n: i32) -> i32 {
+    benchmark(n as usize)
+}